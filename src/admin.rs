@@ -0,0 +1,85 @@
+use std::env;
+use std::sync::RwLock;
+
+use rocket::{
+	Request,
+	State,
+	request::{
+		FromRequest,
+		Outcome as RequestOutcome
+	},
+	outcome::Outcome,
+	http::Status
+};
+
+use data::GameData;
+
+/*
+ * Environment variable holding the directory the game data is loaded from.
+ */
+pub const DATA_DIR_VAR: &'static str = "GAME_DATA_DIR";
+
+/*
+ * Environment variable holding the token admin requests have to pass in the "Admin-Token" header.
+ */
+const ADMIN_TOKEN_VAR: &'static str = "ADMIN_TOKEN";
+
+/*
+ * Request guard which only succeeds if the request carries the admin token.
+ * Admin requests are always rejected if no admin token is configured.
+ */
+pub struct AdminToken;
+
+impl<'a, 'r> FromRequest<'a, 'r> for AdminToken {
+	type Error = ();
+	
+	fn from_request(request: &'a Request<'r>) -> RequestOutcome<Self, Self::Error> {
+		let expected = env::var(ADMIN_TOKEN_VAR).unwrap_or_default();
+		let token = request.headers().get_one("Admin-Token");
+		
+		match token {
+			Some(token) if !expected.is_empty() && token == expected => Outcome::Success(AdminToken),
+			_ => Outcome::Failure((Status::Forbidden, ()))
+		}
+	}
+}
+
+/*
+ * Request handler for reloading the game data.
+ * Reads the JSON files from the data directory again. If they are broken or contain invalid values, the old data is kept.
+ * "data_lock" is the GameData struct kept persistent between requests by Rocket.
+ * Only accepts the request if the admin token is present.
+ */
+#[post("/admin/reload")]
+pub fn reload(data_lock: State<RwLock<GameData>>, _admin_token: AdminToken) -> String {
+	let dir = if let Ok(dir) = env::var(DATA_DIR_VAR) {
+		dir
+	} else {
+		return format!("{} is not set, nothing to reload.", DATA_DIR_VAR);
+	};
+	
+	let loaded = GameData::load(&dir).and_then(|data| {
+		let errors = data.validate();
+		
+		if errors.is_empty() {
+			Ok(data)
+		} else {
+			Err(errors.join("; "))
+		}
+	});
+	
+	match loaded {
+		Ok(data) => {
+			let summary = format!("Reloaded {} civs, {} units, {} techs and {} buildings from {}.", data.civs.len(), data.units.len(), data.techs.len(), data.buildings.len(), dir);
+			
+			*data_lock.write().unwrap() = data;
+			
+			summary
+		},
+		Err(e) => {
+			eprintln!("Could not reload game data from {}: {}", dir, e);
+			
+			format!("Kept the old game data, {} is broken: {}", dir, e)
+		}
+	}
+}
@@ -0,0 +1,116 @@
+use std::sync::RwLock;
+
+use rocket::State;
+
+use util::{
+	self,
+	NightbotHeaderFields
+};
+use config::ChannelConfig;
+use data::{
+	GameData,
+	GameVersion,
+	Entity,
+	Age
+};
+
+/*
+ * Additional information passed as query parameters.
+ */
+#[derive(FromForm)]
+pub struct AgeInfo {
+	version: Option<String>
+}
+
+/*
+ * Lists the names of the entities which are first available in the given age.
+ * Buildings are listed once per age, so only their earliest age counts.
+ */
+fn first_available_in<'a, I>(entities: I, age: Age) -> Vec<&'a str> where I: Iterator<Item = Entity<'a>> {
+	let mut earliest: Vec<(&str, Age)> = Vec::new();
+	
+	for entity in entities {
+		if let Some(position) = earliest.iter().position(|&(name, _)| name == entity.name()) {
+			earliest[position].1 = earliest[position].1.min(entity.age());
+		} else {
+			earliest.push((entity.name(), entity.age()));
+		}
+	}
+	
+	earliest.into_iter()
+		.filter(|&(_, earliest_age)| earliest_age == age)
+		.map(|(name, _)| name)
+		.collect()
+}
+
+/*
+ * Lists the units, buildings and techs the specified civ gets in an age.
+ */
+fn fetch_age_data<S, T>(data: &GameData, age_name: S, civ_name: T, version: Option<GameVersion>) -> String where S: AsRef<str>, T: AsRef<str> {
+	let age = if let Some(age) = Age::parse(&age_name) {
+		age
+	} else {
+		return String::from("That age does not exist.");
+	};
+	let civ = if let Some(civ) = data.civ_by_name(&civ_name) {
+		civ
+	} else {
+		return util::with_suggestions("That civ does not exist.", &data.civ_suggestions(&civ_name));
+	};
+	let correction = data.correction(civ_name.as_ref(), &civ.name);
+	
+	if let Some(not_in_version) = util::not_in_version(&civ.name, civ.version(), version) {
+		return format!("{}{}", correction, not_in_version);
+	}
+	
+	let available = |entity: &Entity| {
+		let in_version = version.map_or(true, |version| version.includes(entity.version()));
+		let available = entity.available_to(&civ.name).map_or(true, |(available, _)| available);
+		
+		in_version && available
+	};
+	let units = first_available_in(data.units.iter().map(Entity::Unit).filter(&available), age);
+	let buildings = first_available_in(data.buildings.iter().map(Entity::Building).filter(&available), age);
+	let techs = first_available_in(data.techs.iter().map(Entity::Tech).filter(&available), age);
+	let sections = [("units", units), ("buildings", buildings), ("techs", techs)].iter()
+		.filter(|(_, names)| !names.is_empty())
+		.map(|(section, names)| format!("{}: {}", section, names.join(", ")))
+		.collect::<Vec<_>>();
+	
+	if sections.is_empty() {
+		format!("{}{} get nothing new in the {}.", correction, civ.name, age)
+	} else {
+		format!("{}{} in the {}: {}.", correction, civ.name, age, sections.join("; "))
+	}
+}
+
+/*
+ * Request handler for the age resource.
+ * Lists what becomes available for the specified civ in an age.
+ * "data_lock" is the GameData struct kept persistent between requests by Rocket.
+ * "config" is the channel config kept persistent between requests by Rocket.
+ * Only accepts the request if the Nightbot headers are present.
+ */
+#[get("/age/<age>/<civ>")]
+pub fn age(data_lock: State<RwLock<GameData>>, config: State<ChannelConfig>, age: String, civ: String, nightbot_headers: NightbotHeaderFields) -> String {
+	age_with_info(data_lock, config, age, civ, AgeInfo { version: None }, nightbot_headers)
+}
+
+/*
+ * Request handler for the age resource with additional information.
+ * Lists what becomes available for the specified civ in an age of the requested game version.
+ * "data_lock" is the GameData struct kept persistent between requests by Rocket.
+ * "config" is the channel config kept persistent between requests by Rocket.
+ * "age_info" are the query parameters (version). They might be None.
+ * Only accepts the request if the Nightbot headers are present.
+ */
+#[get("/age/<age>/<civ>?<age_info>")]
+pub fn age_with_info(data_lock: State<RwLock<GameData>>, config: State<ChannelConfig>, age: String, civ: String, age_info: AgeInfo, nightbot_headers: NightbotHeaderFields) -> String {
+	let data = data_lock.read().unwrap();
+	let age_data = match config.game_version(&nightbot_headers, age_info.version.as_ref()) {
+		Ok(version) => fetch_age_data(&data, &age, &civ, version),
+		Err(_) => String::from("That game version does not exist.")
+	};
+	
+	util::create_response(age_data, &nightbot_headers)
+}
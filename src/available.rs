@@ -1,9 +1,82 @@
 use std::sync::RwLock;
 
-use rocket::State;
+use rocket::{
+	Request,
+	State,
+	request::{
+		FromRequest,
+		Outcome as RequestOutcome
+	},
+	outcome::Outcome
+};
+use url::form_urlencoded;
 
-use util::{self, NightbotHeaderFields};
-use data::GameData;
+use util::{self, NightbotHeaderFields, OutputFormat, Output};
+use config::ChannelConfig;
+use data::{
+	GameData,
+	GameVersion,
+	Entity
+};
+
+/*
+ * Additional information passed as query parameters.
+ */
+#[derive(FromForm)]
+pub struct AvailableInfo {
+	version: Option<String>,
+	/* Read by the OutputFormat guard */
+	#[allow(dead_code)]
+	format: Option<String>
+}
+
+/*
+ * JSON payload of the available resource.
+ */
+#[derive(Serialize)]
+struct AvailablePayload<'a> {
+	civ: String,
+	entity: &'a str,
+	available: bool
+}
+
+/*
+ * Query parameters of the who resource.
+ * Read from the raw query like OutputFormat, because Rocket's query forms reject a parameter without "=" like "?missing".
+ */
+pub struct WhoInfo {
+	missing: bool,
+	version: Option<String>
+}
+
+impl<'a, 'r> FromRequest<'a, 'r> for WhoInfo {
+	type Error = ();
+	
+	fn from_request(request: &'a Request<'r>) -> RequestOutcome<Self, Self::Error> {
+		let mut who_info = WhoInfo {
+			missing: false,
+			version: None
+		};
+		
+		if let Some(query) = request.uri().query() {
+			for (key, value) in form_urlencoded::parse(query.as_bytes()) {
+				match key.as_ref() {
+					/* "?missing" without a value counts as true */
+					"missing" => who_info.missing = value.is_empty() || value == "true" || value == "on",
+					"version" => who_info.version = Some(value.into_owned()),
+					_ => ()
+				}
+			}
+		}
+		
+		Outcome::Success(who_info)
+	}
+}
+
+/*
+ * Civ lists longer than this are written as "all civs except ..." if that is shorter.
+ */
+const LONG_CIV_LIST: usize = 5;
 
 /*
  * Result when checking if a civ has an entity.
@@ -11,6 +84,7 @@ use data::GameData;
 enum AvailableResult {
 	Yes(String, String),
 	No(String, String),
+	NotInVersion(String),
 	InvalidCiv,
 	InvalidEntity
 }
@@ -43,42 +117,33 @@ fn plural_lowercase(mut s: String) -> String {
 /*
  * Checks whether the specified civ has an entity or not.
  */
-fn fetch_civ_has_entity<S, T>(data: &GameData, civ: S, name: T) -> AvailableResult where S: AsRef<str>, T: AsRef<str> {
-	if let Some(unit) = data.unit_by_name(&name) {
-		let civ_name = unit.available_to(&civ);
-		let unit_name = plural_lowercase(unit.name.clone());
-		
-		if let Some((true, civ_name)) = civ_name {
-			AvailableResult::Yes(civ_name, unit_name)
-		} else if let Some((false, civ_name)) = civ_name {
-			AvailableResult::No(civ_name, unit_name)
-		} else {
-			AvailableResult::InvalidCiv
-		}
-	} else if let Some(building) = data.building_by_name(&name) {
-		let civ_name = building.available_to(&civ);
-		let building_name = plural_lowercase(building.name.clone());
-		
-		if let Some((true, civ_name)) = civ_name {
-			AvailableResult::Yes(civ_name, building_name)
-		} else if let Some((false, civ_name)) = civ_name {
-			AvailableResult::No(civ_name, building_name)
-		} else {
-			AvailableResult::InvalidCiv
-		}
-	} else if let Some(tech) = data.tech_by_name(&name) {
-		let civ_name = tech.available_to(&civ);
-		let tech_name = tech.name.clone();
-		
-		if let Some((true, civ_name)) = civ_name {
-			AvailableResult::Yes(civ_name, tech_name)
-		} else if let Some((false, civ_name)) = civ_name {
-			AvailableResult::No(civ_name, tech_name)
-		} else {
-			AvailableResult::InvalidCiv
-		}
+fn fetch_civ_has_entity<S, T>(data: &GameData, civ: S, name: T, version: Option<GameVersion>) -> AvailableResult where S: AsRef<str>, T: AsRef<str> {
+	let entity = if let Some(entity) = data.entity_by_name(&name) {
+		entity
+	} else {
+		return AvailableResult::InvalidEntity;
+	};
+	let civ = if let Some(civ) = data.civ_by_name(&civ) {
+		civ
+	} else {
+		return AvailableResult::InvalidCiv;
+	};
+	let not_in_version = util::not_in_version(entity.name(), entity.version(), version)
+		.or_else(|| util::not_in_version(&civ.name, civ.version(), version));
+	
+	if let Some(not_in_version) = not_in_version {
+		return AvailableResult::NotInVersion(not_in_version);
+	}
+	let entity_name = if let Entity::Tech(tech) = entity {
+		tech.name.clone()
 	} else {
-		AvailableResult::InvalidEntity
+		plural_lowercase(entity.name().to_string())
+	};
+	
+	match entity.available_to(&civ.name) {
+		Some((true, civ_name)) => AvailableResult::Yes(civ_name, entity_name),
+		Some((false, civ_name)) => AvailableResult::No(civ_name, entity_name),
+		None => AvailableResult::InvalidCiv
 	}
 }
 
@@ -86,18 +151,104 @@ fn fetch_civ_has_entity<S, T>(data: &GameData, civ: S, name: T) -> AvailableResu
  * Request handler for the availble resource.
  * Checks if the specified civ has that entity.
  * "data_lock" is the GameData struct kept persistent between requests by Rocket.
+ * "config" is the channel config kept persistent between requests by Rocket.
+ * "format" is the requested output format.
  * Only accepts the request if the Nightbot headers are present.
  */
 #[get("/available/<civ>/<entity>")]
-pub fn available(data_lock: State<RwLock<GameData>>, civ: String, entity: String, nightbot_headers: NightbotHeaderFields) -> String {
+pub fn available(data_lock: State<RwLock<GameData>>, config: State<ChannelConfig>, civ: String, entity: String, format: OutputFormat, nightbot_headers: NightbotHeaderFields) -> Output {
+	available_with_info(data_lock, config, civ, entity, AvailableInfo{version: None, format: None}, format, nightbot_headers)
+}
+
+/*
+ * Request handler for the availble resource with additional information.
+ * Checks if the specified civ has that entity in the requested game version.
+ * "data_lock" is the GameData struct kept persistent between requests by Rocket.
+ * "config" is the channel config kept persistent between requests by Rocket.
+ * "format" is the requested output format.
+ * Only accepts the request if the Nightbot headers are present.
+ */
+#[get("/available/<civ>/<entity>?<available_info>")]
+pub fn available_with_info(data_lock: State<RwLock<GameData>>, config: State<ChannelConfig>, civ: String, entity: String, available_info: AvailableInfo, format: OutputFormat, nightbot_headers: NightbotHeaderFields) -> Output {
 	let data = data_lock.read().unwrap();
+	let found = data.entity_by_name(&entity);
+	let correction = found
+		.map(|found| data.correction(&entity, found.name()))
+		.unwrap_or_else(String::new);
+	let entity_name = found.map_or("", |found| found.name());
+	let version = match config.game_version(&nightbot_headers, available_info.version.as_ref()) {
+		Ok(version) => version,
+		Err(_) => return util::create_output::<AvailablePayload>(Err(String::from("That game version does not exist.")), format, &nightbot_headers)
+	};
+	
+	let entity_info = match fetch_civ_has_entity(&data, &civ, &entity, version) {
+		AvailableResult::Yes(civ, entity) => Ok((format!("{}{} do have {}!", correction, civ, entity), AvailablePayload { civ: civ, entity: entity_name, available: true })),
+		AvailableResult::No(civ, entity) => Ok((format!("{}{} do not have {}.", correction, civ, entity), AvailablePayload { civ: civ, entity: entity_name, available: false })),
+		AvailableResult::NotInVersion(answer) => Err(format!("{}{}", correction, answer)),
+		AvailableResult::InvalidCiv => Err(util::with_suggestions("That civ does not exist.", &data.civ_suggestions(&civ))),
+		AvailableResult::InvalidEntity => Err(util::with_suggestions("That tech / unit / building does not exist.", &data.entity_suggestions(&entity)))
+	};
 	
-	let entity_info = match fetch_civ_has_entity(&data, &civ, &entity) {
-		AvailableResult::Yes(civ, entity) => format!("{} do have {}!", civ, entity),
-		AvailableResult::No(civ, entity) => format!("{} do not have {}.", civ, entity),
-		AvailableResult::InvalidCiv => String::from("That civ does not exist."),
-		AvailableResult::InvalidEntity => String::from("That tech / unit / building does not exist.")
+	util::create_output(entity_info, format, &nightbot_headers)
+}
+
+/*
+ * Lists the civs which have (or lack, if "missing" is set) the specified entity.
+ */
+fn fetch_civs_with_entity<S>(data: &GameData, name: S, missing: bool, version: Option<GameVersion>) -> String where S: AsRef<str> {
+	let entity = if let Some(entity) = data.entity_by_name(&name) {
+		entity
+	} else {
+		return util::with_suggestions("That tech / unit / building does not exist.", &data.entity_suggestions(&name));
+	};
+	let correction = data.correction(name.as_ref(), entity.name());
+	
+	if let Some(not_in_version) = util::not_in_version(entity.name(), entity.version(), version) {
+		return format!("{}{}", correction, not_in_version);
+	}
+	
+	let (mut listed, mut others) = (Vec::new(), Vec::new());
+	
+	for civ in data.civs.iter().filter(|civ| version.map_or(true, |version| version.includes(civ.version()))) {
+		match entity.available_to(&civ.name) {
+			Some((available, _)) if available != missing => listed.push(civ.name.as_str()),
+			Some(_) => others.push(civ.name.as_str()),
+			None => ()
+		}
+	}
+	
+	let civs = if listed.is_empty() {
+		String::from("none")
+	} else if others.is_empty() {
+		String::from("all civs")
+	} else if listed.len() > LONG_CIV_LIST && others.len() < listed.len() {
+		format!("all civs except {}", others.join(", "))
+	} else {
+		listed.join(", ")
+	};
+	
+	if missing {
+		format!("{}Civs without {}: {}.", correction, entity.name(), civs)
+	} else {
+		format!("{}Civs with {}: {}.", correction, entity.name(), civs)
+	}
+}
+
+/*
+ * Request handler for the who resource.
+ * Lists all civs which have the specified entity or, if "missing" is set, all civs which lack it.
+ * "data_lock" is the GameData struct kept persistent between requests by Rocket.
+ * "config" is the channel config kept persistent between requests by Rocket.
+ * "who_info" are the query parameters (missing, version), read from the query by the guard.
+ * Only accepts the request if the Nightbot headers are present.
+ */
+#[get("/who/<entity>")]
+pub fn who(data_lock: State<RwLock<GameData>>, config: State<ChannelConfig>, entity: String, who_info: WhoInfo, nightbot_headers: NightbotHeaderFields) -> String {
+	let data = data_lock.read().unwrap();
+	let civs_info = match config.game_version(&nightbot_headers, who_info.version.as_ref()) {
+		Ok(version) => fetch_civs_with_entity(&data, &entity, who_info.missing, version),
+		Err(_) => String::from("That game version does not exist.")
 	};
 	
-	util::create_response(entity_info, &nightbot_headers)
+	util::create_response(civs_info, &nightbot_headers)
 }
@@ -4,40 +4,150 @@ use rocket::State;
 
 use util::{
 	self,
-	NightbotHeaderFields
+	NightbotHeaderFields,
+	OutputFormat,
+	Output
 };
-use data::GameData;
-
-fn map_age<S>(age: S) -> String where S: AsRef<str> {
-	match age.as_ref() {
-		"0" => "dark age",
-		"1" => "feudal age",
-		"2" => "castle age",
-		"3" => "imperial age",
-		_ => "?"
-	}.to_string()
+use data::{
+	GameData,
+	GameVersion,
+	Building
+};
+use config::ChannelConfig;
+use template::Templates;
+
+/*
+ * Default answer templates. Channels can override them with the keys "building" and "building_ranged".
+ */
+const BUILDING_TEMPLATE: &'static str = "{name}{version} costs {cost}, is available in {age} and takes {time} to build.";
+const BUILDING_RANGED_TEMPLATE: &'static str = "{name}{version} costs {cost}, is available in {age}, takes {time} to build and has {range} range.";
+
+/*
+ * Possible query parameters passed to the building resource.
+ */
+#[derive(FromForm)]
+pub struct BuildingInfo {
+	civ: Option<String>,
+	version: Option<String>,
+	/* Read by the OutputFormat guard */
+	#[allow(dead_code)]
+	format: Option<String>
+}
+
+/*
+ * JSON payload of the building resource.
+ */
+#[derive(Serialize)]
+struct BuildingPayload<'a> {
+	building: &'a Building,
+	civ: Option<&'a str>,
+	available: bool,
+	civ_notes: Vec<String>
+}
+
+/*
+ * Fetches the data for the specified building name.
+ * If a civ is given, the civ specific notes are appended.
+ * If a game version is given, only things that are part of it are considered.
+ * The chat text is rendered from the channel's templates.
+ * Returns the chat text and the JSON payload, or the answer if the building could not be looked up.
+ */
+fn fetch_building_data<'a, S>(data: &'a GameData, name: S, civ: Option<&String>, version: Option<GameVersion>, templates: &Templates) -> Result<(String, BuildingPayload<'a>), String> where S: AsRef<str> {
+	let building = if let Some(building) = data.building_by_name(&name) {
+		building
+	} else {
+		return Err(util::with_suggestions("That building does not exist.", &data.building_suggestions(&name)));
+	};
+	let civ = if let Some(civ) = civ {
+		if let Some(civ) = data.civ_by_name(civ) {
+			Some(civ)
+		} else {
+			return Err(util::with_suggestions("That civ does not exist.", &data.civ_suggestions(civ)));
+		}
+	} else {
+		None
+	};
+	let correction = data.correction(name.as_ref(), &building.name);
+	let not_in_version = util::not_in_version(&building.name, building.version(), version)
+		.or_else(|| civ.and_then(|civ| util::not_in_version(&civ.name, civ.version(), version)));
+	
+	if let Some(not_in_version) = not_in_version {
+		return Err(format!("{}{}", correction, not_in_version));
+	}
+	
+	let mut payload = BuildingPayload {
+		building: building,
+		civ: civ.map(|civ| civ.name.as_str()),
+		available: true,
+		civ_notes: Vec::new()
+	};
+	
+	if let Some(civ) = civ {
+		if let Some((false, civ_name)) = building.available_to(&civ.name) {
+			payload.available = false;
+			
+			return Ok((format!("{}{} cannot build {}.", correction, civ_name, building.name), payload));
+		}
+	}
+	
+	let version_name = building.version().map(|version| format!(" ({})", version.name())).unwrap_or_default();
+	let values = [
+		("name", building.name.clone()),
+		("version", version_name),
+		("cost", building.cost.to_string()),
+		("age", building.age.to_string()),
+		("time", building.building_time.clone()),
+		("range", building.range.clone())
+	];
+	let building_info = if !building.range.is_empty() && building.range != "-" {
+		templates.render("building_ranged", BUILDING_RANGED_TEMPLATE, &values)
+	} else {
+		templates.render("building", BUILDING_TEMPLATE, &values)
+	};
+	let civ_notes = civ.map(|civ| building.civ_notes(&civ.name)).unwrap_or_else(Vec::new);
+	let building_info = if civ_notes.is_empty() {
+		format!("{}{}", correction, building_info)
+	} else {
+		format!("{}{} {}.", correction, building_info, civ_notes.join("; "))
+	};
+	
+	payload.civ_notes = civ_notes;
+	
+	Ok((building_info, payload))
 }
 
 /*
  * Request handler for the building resource.
  * Grabs the specified building from the game data and processes it.
  * "data_lock" is the GameData struct kept persistent between requests by Rocket.
+ * "config" is the channel config kept persistent between requests by Rocket.
+ * "format" is the requested output format.
  * Only accepts the request if the Nightbot headers are present.
  */
 #[get("/building/<name>")]
-pub fn building(data_lock: State<RwLock<GameData>>, name: String, nightbot_headers: NightbotHeaderFields) -> String {
+pub fn building(data_lock: State<RwLock<GameData>>, config: State<ChannelConfig>, name: String, format: OutputFormat, nightbot_headers: NightbotHeaderFields) -> Output {
+	let building_info = BuildingInfo { civ: None, version: None, format: None };
+	
+	building_with_info(data_lock, config, name, building_info, format, nightbot_headers)
+}
+
+/*
+ * Request handler for the building resource.
+ * Grabs the specified building from the game data and processes it.
+ * "data_lock" is the GameData struct kept persistent between requests by Rocket.
+ * "config" is the channel config kept persistent between requests by Rocket.
+ * "building_info" are the query parameters (civ, version, format). They might be None.
+ * "format" is the requested output format.
+ * Only accepts the request if the Nightbot headers are present.
+ */
+#[get("/building/<name>?<building_info>")]
+pub fn building_with_info(data_lock: State<RwLock<GameData>>, config: State<ChannelConfig>, name: String, building_info: BuildingInfo, format: OutputFormat, nightbot_headers: NightbotHeaderFields) -> Output {
 	let data = data_lock.read().unwrap();
-	let building_info = if let Some(building) = data.building_by_name(&name) {
-		let age = map_age(&building.age);
-		
-		if !building.range.is_empty() && building.range != "-" {
-			format!("{} costs {}, is available in {}, takes {} to build and has {} range.", building.name, building.cost, age, building.building_time, building.range)
-		} else {
-			format!("{} costs {}, is available in {} and takes {} to build.", building.name, building.cost, age, building.building_time)
-		}
-	} else {
-		String::from("That building does not exist.")
+	let templates = config.templates(&nightbot_headers);
+	let building_info = match config.game_version(&nightbot_headers, building_info.version.as_ref()) {
+		Ok(version) => fetch_building_data(&data, &name, building_info.civ.as_ref(), version, &templates),
+		Err(_) => Err(String::from("That game version does not exist."))
 	};
 	
-	util::create_response(building_info, &nightbot_headers)
+	util::create_output(building_info, format, &nightbot_headers)
 }
@@ -0,0 +1,191 @@
+use std::collections::HashMap;
+use std::hash::Hash;
+use std::env;
+use std::time::{
+	Instant,
+	Duration
+};
+
+/*
+ * Map with a maximum number of entries which expire a fixed duration after being inserted, reading them does not extend that.
+ * Inserting into a full cache drops all expired entries and, if it is still full, the entry read or inserted longest ago.
+ * Eviction scans all entries, so it takes O(n) time. That is fine for caches of a few thousand entries.
+ */
+pub struct Cache<K, V> {
+	entries: HashMap<K, Entry<V>>,
+	capacity: usize,
+	ttl: Duration,
+	/* Incremented on every access, entries remember when they were used last */
+	clock: u64
+}
+
+struct Entry<V> {
+	value: V,
+	inserted: Instant,
+	last_used: u64
+}
+
+impl<K, V> Cache<K, V> where K: Clone + Eq + Hash, V: Clone {
+	pub fn new(capacity: usize, ttl: Duration) -> Self {
+		Cache {
+			entries: HashMap::new(),
+			capacity: capacity,
+			ttl: ttl,
+			clock: 0
+		}
+	}
+	
+	/*
+	 * Creates a cache configured by the environment variables "<PREFIX>_CACHE_SIZE" (number of entries) and "<PREFIX>_CACHE_SECONDS".
+	 * Falls back to the defaults if a variable is not set or invalid.
+	 */
+	pub fn from_env(prefix: &str, default_capacity: usize, default_ttl: Duration) -> Self {
+		let capacity = env_var(&format!("{}_CACHE_SIZE", prefix)).map(|size| size as usize).unwrap_or(default_capacity);
+		let ttl = env_var(&format!("{}_CACHE_SECONDS", prefix)).map(Duration::from_secs).unwrap_or(default_ttl);
+		
+		Self::new(capacity, ttl)
+	}
+	
+	/*
+	 * Gets the value for the key if it has not expired yet.
+	 */
+	pub fn get(&mut self, key: &K) -> Option<V> {
+		let ttl = self.ttl;
+		let expired = self.entries.get(key).map_or(false, |entry| entry.inserted.elapsed() >= ttl);
+		
+		if expired {
+			self.entries.remove(key);
+			
+			return None;
+		}
+		
+		self.clock += 1;
+		
+		let clock = self.clock;
+		
+		self.entries.get_mut(key).map(|entry| {
+			entry.last_used = clock;
+			
+			entry.value.clone()
+		})
+	}
+	
+	/*
+	 * Inserts or replaces the value for the key, evicting entries if the cache is full.
+	 */
+	pub fn insert(&mut self, key: K, value: V) {
+		if self.capacity == 0 {
+			return;
+		}
+		
+		if !self.entries.contains_key(&key) && self.entries.len() >= self.capacity {
+			self.evict();
+		}
+		
+		self.clock += 1;
+		self.entries.insert(key, Entry {
+			value: value,
+			inserted: Instant::now(),
+			last_used: self.clock
+		});
+	}
+	
+	/*
+	 * Drops all expired entries and, if the cache is still full, the entry read or inserted longest ago.
+	 */
+	fn evict(&mut self) {
+		let ttl = self.ttl;
+		
+		self.entries.retain(|_, entry| entry.inserted.elapsed() < ttl);
+		
+		if self.entries.len() >= self.capacity {
+			let least_recently_used = self.entries.iter()
+				.min_by_key(|(_, entry)| entry.last_used)
+				.map(|(key, _)| key.clone());
+			
+			if let Some(key) = least_recently_used {
+				self.entries.remove(&key);
+			}
+		}
+	}
+}
+
+/*
+ * Parses a numeric environment variable. Invalid values are reported and ignored.
+ */
+fn env_var(name: &str) -> Option<u64> {
+	let value = env::var(name).ok()?;
+	
+	match value.parse() {
+		Ok(value) => Some(value),
+		Err(_) => {
+			eprintln!("{} is not a number: {}", name, value);
+			
+			None
+		}
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use std::thread;
+	
+	#[test]
+	fn get_inserted() {
+		let mut cache = Cache::new(2, Duration::from_secs(60));
+		
+		cache.insert("a", 1);
+		cache.insert("a", 2);
+		
+		assert_eq!(cache.get(&"a"), Some(2));
+		assert_eq!(cache.get(&"b"), None);
+	}
+	
+	#[test]
+	fn expire() {
+		let mut cache = Cache::new(2, Duration::from_millis(20));
+		
+		cache.insert("a", 1);
+		thread::sleep(Duration::from_millis(40));
+		
+		assert_eq!(cache.get(&"a"), None);
+	}
+	
+	#[test]
+	fn evict_least_recently_used() {
+		let mut cache = Cache::new(2, Duration::from_secs(60));
+		
+		cache.insert("a", 1);
+		cache.insert("b", 2);
+		cache.get(&"a");
+		cache.insert("c", 3);
+		
+		assert_eq!(cache.get(&"a"), Some(1));
+		assert_eq!(cache.get(&"b"), None);
+		assert_eq!(cache.get(&"c"), Some(3));
+	}
+	
+	#[test]
+	fn evict_expired_first() {
+		let mut cache = Cache::new(2, Duration::from_millis(20));
+		
+		cache.insert("a", 1);
+		thread::sleep(Duration::from_millis(40));
+		cache.insert("b", 2);
+		cache.insert("c", 3);
+		
+		assert_eq!(cache.entries.len(), 2);
+		assert_eq!(cache.get(&"b"), Some(2));
+		assert_eq!(cache.get(&"c"), Some(3));
+	}
+	
+	#[test]
+	fn capacity_zero() {
+		let mut cache = Cache::new(0, Duration::from_secs(60));
+		
+		cache.insert("a", 1);
+		
+		assert_eq!(cache.get(&"a"), None);
+	}
+}
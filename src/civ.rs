@@ -0,0 +1,159 @@
+use std::sync::RwLock;
+
+use rocket::State;
+
+use util::{
+	self,
+	NightbotHeaderFields
+};
+use data::GameData;
+
+/*
+ * Request handler for the civ resource.
+ * Grabs the specified civ from the game data and summarizes it.
+ * "data_lock" is the GameData struct kept persistent between requests by Rocket.
+ * Only accepts the request if the Nightbot headers are present.
+ */
+#[get("/civ/<name>")]
+pub fn civ(data_lock: State<RwLock<GameData>>, name: String, nightbot_headers: NightbotHeaderFields) -> String {
+	let data = data_lock.read().unwrap();
+	let civ_info = if let Some(civ) = data.civ_by_name(&name) {
+		format!("{}{}: {} civ. Unique unit: {}, unique techs: {}. Team bonus: {}.", data.correction(&name, &civ.name), civ.name, civ.strength, civ.unique_unit, civ.unique_tech, civ.team_bonus.trim_right_matches('.'))
+	} else {
+		util::with_suggestions("That civ does not exist.", &data.civ_suggestions(&name))
+	};
+	
+	util::create_response(civ_info, &nightbot_headers)
+}
+
+/*
+ * Possible query parameters passed to the civ bonus resource.
+ */
+#[derive(FromForm)]
+pub struct CivBonusInfo {
+	n: Option<usize>
+}
+
+/*
+ * Fetches the boni of the specified civ.
+ * If n is given, only the n-th bonus (starting at 1) is returned.
+ */
+fn fetch_civ_boni<S>(data: &GameData, name: S, n: Option<usize>) -> String where S: AsRef<str> {
+	if let Some(civ) = data.civ_by_name(&name) {
+		let boni = civ.boni();
+		
+		if let Some(n) = n {
+			if let Some(bonus) = n.checked_sub(1).and_then(|i| boni.get(i)) {
+				format!("{} bonus {}/{}: {}.", civ.name, n, boni.len(), bonus)
+			} else {
+				format!("{} only have {} boni.", civ.name, boni.len())
+			}
+		} else {
+			let boni = boni.iter()
+				.enumerate()
+				.map(|(i, bonus)| format!("{}) {}", i + 1, bonus))
+				.collect::<Vec<_>>()
+				.join("; ");
+			
+			format!("{} boni: {}.", civ.name, boni)
+		}
+	} else {
+		util::with_suggestions("That civ does not exist.", &data.civ_suggestions(&name))
+	}
+}
+
+/*
+ * Request handler for the civ bonus resource.
+ * Lists all boni of the specified civ.
+ * "data_lock" is the GameData struct kept persistent between requests by Rocket.
+ * Only accepts the request if the Nightbot headers are present.
+ */
+#[get("/civ/<name>/bonus")]
+pub fn civ_bonus(data_lock: State<RwLock<GameData>>, name: String, nightbot_headers: NightbotHeaderFields) -> String {
+	let bonus_info = CivBonusInfo { n: None };
+	
+	civ_bonus_with_info(data_lock, name, bonus_info, nightbot_headers)
+}
+
+/*
+ * Request handler for the civ bonus resource.
+ * Lists all boni of the specified civ or only the one selected by "n".
+ * "data_lock" is the GameData struct kept persistent between requests by Rocket.
+ * "bonus_info" are the query parameters (n). They might be None.
+ * Only accepts the request if the Nightbot headers are present.
+ */
+#[get("/civ/<name>/bonus?<bonus_info>")]
+pub fn civ_bonus_with_info(data_lock: State<RwLock<GameData>>, name: String, bonus_info: CivBonusInfo, nightbot_headers: NightbotHeaderFields) -> String {
+	let data = data_lock.read().unwrap();
+	let boni = fetch_civ_boni(&data, &name, bonus_info.n);
+	
+	util::create_response(boni, &nightbot_headers)
+}
+
+/*
+ * Request handler for the civ team bonus resource.
+ * "data_lock" is the GameData struct kept persistent between requests by Rocket.
+ * Only accepts the request if the Nightbot headers are present.
+ */
+#[get("/civ/<name>/teambonus")]
+pub fn civ_team_bonus(data_lock: State<RwLock<GameData>>, name: String, nightbot_headers: NightbotHeaderFields) -> String {
+	let data = data_lock.read().unwrap();
+	let team_bonus = if let Some(civ) = data.civ_by_name(&name) {
+		format!("{} team bonus: {}.", civ.name, civ.team_bonus.trim_right_matches('.'))
+	} else {
+		util::with_suggestions("That civ does not exist.", &data.civ_suggestions(&name))
+	};
+	
+	util::create_response(team_bonus, &nightbot_headers)
+}
+
+/*
+ * Request handler for the civ unique unit resource.
+ * Adds the unit description if the unique unit is part of the game data.
+ * "data_lock" is the GameData struct kept persistent between requests by Rocket.
+ * Only accepts the request if the Nightbot headers are present.
+ */
+#[get("/civ/<name>/uu")]
+pub fn civ_unique_unit(data_lock: State<RwLock<GameData>>, name: String, nightbot_headers: NightbotHeaderFields) -> String {
+	let data = data_lock.read().unwrap();
+	let unique_unit = if let Some(civ) = data.civ_by_name(&name) {
+		if let Some(unit) = data.unit_by_name(&civ.unique_unit).filter(|unit| !unit.note.is_empty()) {
+			format!("{} unique unit: {}. {}", civ.name, unit.name, unit.note)
+		} else {
+			format!("{} unique unit: {}.", civ.name, civ.unique_unit)
+		}
+	} else {
+		util::with_suggestions("That civ does not exist.", &data.civ_suggestions(&name))
+	};
+	
+	util::create_response(unique_unit, &nightbot_headers)
+}
+
+/*
+ * Request handler for the civ unique tech resource.
+ * Adds the tech effects if the unique techs are part of the game data.
+ * "data_lock" is the GameData struct kept persistent between requests by Rocket.
+ * Only accepts the request if the Nightbot headers are present.
+ */
+#[get("/civ/<name>/ut")]
+pub fn civ_unique_tech(data_lock: State<RwLock<GameData>>, name: String, nightbot_headers: NightbotHeaderFields) -> String {
+	let data = data_lock.read().unwrap();
+	let unique_techs = if let Some(civ) = data.civ_by_name(&name) {
+		let techs = civ.unique_techs().iter()
+			.map(|name| {
+				if let Some(tech) = data.tech_by_name(name).filter(|tech| !tech.for_what.is_empty()) {
+					format!("{} ({})", tech.name, tech.for_what)
+				} else {
+					name.to_string()
+				}
+			})
+			.collect::<Vec<_>>()
+			.join(", ");
+		
+		format!("{} unique techs: {}.", civ.name, techs)
+	} else {
+		util::with_suggestions("That civ does not exist.", &data.civ_suggestions(&name))
+	};
+	
+	util::create_response(unique_techs, &nightbot_headers)
+}
@@ -0,0 +1,202 @@
+use std::collections::HashMap;
+use std::hash::Hash;
+use std::sync::{
+	Arc,
+	Mutex,
+	Condvar
+};
+
+/*
+ * State of a fetch other requests can wait for.
+ */
+enum FlightState<V> {
+	Running,
+	Done(V),
+	/* The fetching request panicked, waiting requests have to fetch themselves */
+	Abandoned
+}
+
+struct Flight<V> {
+	state: Mutex<FlightState<V>>,
+	finished: Condvar
+}
+
+/*
+ * Coalesces concurrent fetches of the same key.
+ * The first request for a key fetches, requests arriving in the meantime wait for its result instead of fetching again.
+ */
+pub struct Coalescer<K, V> {
+	in_flight: Mutex<HashMap<K, Arc<Flight<V>>>>
+}
+
+/*
+ * Finishes a flight when the fetching request is done, even if it panicked.
+ */
+struct FlightGuard<'a, K, V> where K: 'a + Eq + Hash, V: 'a {
+	coalescer: &'a Coalescer<K, V>,
+	key: &'a K,
+	flight: Arc<Flight<V>>
+}
+
+impl<'a, K, V> Drop for FlightGuard<'a, K, V> where K: 'a + Eq + Hash, V: 'a {
+	fn drop(&mut self) {
+		if let Ok(mut in_flight) = self.coalescer.in_flight.lock() {
+			in_flight.remove(self.key);
+		}
+		
+		if let Ok(mut state) = self.flight.state.lock() {
+			if let FlightState::Running = *state {
+				*state = FlightState::Abandoned;
+			}
+		}
+		
+		self.flight.finished.notify_all();
+	}
+}
+
+impl<K, V> Default for Coalescer<K, V> where K: Clone + Eq + Hash, V: Clone {
+	fn default() -> Self {
+		Coalescer::new()
+	}
+}
+
+impl<K, V> Coalescer<K, V> where K: Clone + Eq + Hash, V: Clone {
+	pub fn new() -> Self {
+		Coalescer {
+			in_flight: Mutex::new(HashMap::new())
+		}
+	}
+	
+	/*
+	 * Runs "fetch" for the key unless a fetch for it is already running, in which case its result is returned.
+	 */
+	pub fn run<F>(&self, key: K, fetch: F) -> V where F: FnOnce() -> V {
+		let (flight, running) = {
+			let mut in_flight = self.in_flight.lock().unwrap();
+			let running = in_flight.get(&key).cloned();
+			
+			if let Some(flight) = running {
+				(flight, true)
+			} else {
+				let flight = Arc::new(Flight {
+					state: Mutex::new(FlightState::Running),
+					finished: Condvar::new()
+				});
+				
+				in_flight.insert(key.clone(), flight.clone());
+				
+				(flight, false)
+			}
+		};
+		
+		if running {
+			{
+				let mut state = flight.state.lock().unwrap();
+				
+				loop {
+					match *state {
+						FlightState::Running => (),
+						FlightState::Done(ref value) => return value.clone(),
+						FlightState::Abandoned => break
+					}
+					
+					state = flight.finished.wait(state).unwrap();
+				}
+			}
+			
+			fetch()
+		} else {
+			let guard = FlightGuard {
+				coalescer: self,
+				key: &key,
+				flight: flight
+			};
+			let value = fetch();
+			
+			*guard.flight.state.lock().unwrap() = FlightState::Done(value.clone());
+			
+			value
+		}
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use std::thread;
+	use std::sync::mpsc;
+	
+	/*
+	 * Blocks until a request waits for the running fetch of the key, which holds a third reference to the flight.
+	 */
+	fn wait_for_waiter(coalescer: &Coalescer<u32, u32>, key: u32) {
+		loop {
+			let waiting = coalescer.in_flight.lock().unwrap()
+				.get(&key)
+				.map_or(false, |flight| Arc::strong_count(flight) >= 3);
+			
+			if waiting {
+				return;
+			}
+			
+			thread::yield_now();
+		}
+	}
+	
+	/*
+	 * Starts a fetch of key 1 which runs "finish" once the returned sender is used.
+	 */
+	fn start_leader<F>(coalescer: &Arc<Coalescer<u32, u32>>, finish: F) -> (mpsc::Sender<()>, thread::JoinHandle<u32>) where F: FnOnce() -> u32 + Send + 'static {
+		let (started_sender, started) = mpsc::channel();
+		let (release, released) = mpsc::channel();
+		let leader_coalescer = coalescer.clone();
+		let leader = thread::spawn(move || leader_coalescer.run(1, move || {
+			started_sender.send(()).unwrap();
+			released.recv().unwrap();
+			
+			finish()
+		}));
+		
+		started.recv().unwrap();
+		
+		(release, leader)
+	}
+	
+	#[test]
+	fn waiter_gets_leader_value() {
+		let coalescer = Arc::new(Coalescer::new());
+		let (release, leader) = start_leader(&coalescer, || 1);
+		let waiter_coalescer = coalescer.clone();
+		let waiter = thread::spawn(move || waiter_coalescer.run(1, || 2));
+		
+		wait_for_waiter(&coalescer, 1);
+		release.send(()).unwrap();
+		
+		assert_eq!(leader.join().unwrap(), 1);
+		assert_eq!(waiter.join().unwrap(), 1);
+		assert!(coalescer.in_flight.lock().unwrap().is_empty());
+	}
+	
+	#[test]
+	fn abandoned_flight_makes_waiter_fetch() {
+		let coalescer = Arc::new(Coalescer::new());
+		let (release, leader) = start_leader(&coalescer, || panic!("fetch failed"));
+		let waiter_coalescer = coalescer.clone();
+		let waiter = thread::spawn(move || waiter_coalescer.run(1, || 2));
+		
+		wait_for_waiter(&coalescer, 1);
+		release.send(()).unwrap();
+		
+		assert!(leader.join().is_err());
+		assert_eq!(waiter.join().unwrap(), 2);
+	}
+	
+	#[test]
+	fn different_keys_fetch_separately() {
+		let coalescer = Coalescer::new();
+		
+		assert_eq!(coalescer.run(1, || 1), 1);
+		assert_eq!(coalescer.run(2, || 2), 2);
+		assert_eq!(coalescer.run(1, || 3), 3);
+	}
+}
@@ -0,0 +1,119 @@
+use std::sync::RwLock;
+
+use rocket::State;
+
+use util::{
+	self,
+	NightbotHeaderFields
+};
+use config::ChannelConfig;
+use data::{
+	GameData,
+	GameVersion,
+	Civ
+};
+
+/*
+ * Additional information passed as query parameters.
+ */
+#[derive(FromForm)]
+pub struct CompareInfo {
+	version: Option<String>
+}
+
+/*
+ * Lists the names of all units, buildings and techs civ "a" has but civ "b" lacks.
+ * Unique units and techs are left out because they always differ.
+ */
+fn only_available_to<'a>(data: &'a GameData, a: &Civ, b: &Civ, version: Option<GameVersion>) -> Vec<&'a str> {
+	let entities = data.entities()
+		.filter(|entity| !entity.is_unique())
+		.filter(|entity| version.map_or(true, |version| version.includes(entity.version())))
+		.filter(|entity| match (entity.available_to(&a.name), entity.available_to(&b.name)) {
+			(Some((true, _)), Some((false, _))) => true,
+			_ => false
+		});
+	let mut names = Vec::new();
+	
+	/* Buildings are listed once per age */
+	for entity in entities {
+		if !names.contains(&entity.name()) {
+			names.push(entity.name());
+		}
+	}
+	
+	names
+}
+
+/*
+ * Compares the specialty, team bonus and tech tree of two civs.
+ */
+fn fetch_comparison<S, T>(data: &GameData, civ_a: S, civ_b: T, version: Option<GameVersion>) -> String where S: AsRef<str>, T: AsRef<str> {
+	let a = if let Some(civ) = data.civ_by_name(&civ_a) {
+		civ
+	} else {
+		return util::with_suggestions("That civ does not exist.", &data.civ_suggestions(&civ_a));
+	};
+	let b = if let Some(civ) = data.civ_by_name(&civ_b) {
+		civ
+	} else {
+		return util::with_suggestions("That civ does not exist.", &data.civ_suggestions(&civ_b));
+	};
+	let correction = format!("{}{}", data.correction(civ_a.as_ref(), &a.name), data.correction(civ_b.as_ref(), &b.name));
+	let not_in_version = util::not_in_version(&a.name, a.version(), version)
+		.or_else(|| util::not_in_version(&b.name, b.version(), version));
+	
+	if let Some(not_in_version) = not_in_version {
+		return format!("{}{}", correction, not_in_version);
+	}
+	
+	let summary = |civ: &Civ| format!("{}: {} civ, team bonus: {}.", civ.name, civ.strength, civ.team_bonus.trim_right_matches('.'));
+	let only = |x: &Civ, y: &Civ| {
+		let names = only_available_to(data, x, y, version);
+		
+		if names.is_empty() {
+			String::new()
+		} else {
+			format!(" Only {}: {}.", x.name, names.join(", "))
+		}
+	};
+	let differences = format!("{}{}", only(a, b), only(b, a));
+	let differences = if differences.is_empty() {
+		String::from(" Same tech tree otherwise.")
+	} else {
+		differences
+	};
+	
+	format!("{}{} {}{}", correction, summary(a), summary(b), differences)
+}
+
+/*
+ * Request handler for the compare resource.
+ * Compares two civs and lists what one has that the other lacks.
+ * "data_lock" is the GameData struct kept persistent between requests by Rocket.
+ * "config" is the channel config kept persistent between requests by Rocket.
+ * Only accepts the request if the Nightbot headers are present.
+ */
+#[get("/compare/<civ_a>/<civ_b>")]
+pub fn compare(data_lock: State<RwLock<GameData>>, config: State<ChannelConfig>, civ_a: String, civ_b: String, nightbot_headers: NightbotHeaderFields) -> String {
+	compare_with_info(data_lock, config, civ_a, civ_b, CompareInfo { version: None }, nightbot_headers)
+}
+
+/*
+ * Request handler for the compare resource with additional information.
+ * Compares two civs in the requested game version.
+ * "data_lock" is the GameData struct kept persistent between requests by Rocket.
+ * "config" is the channel config kept persistent between requests by Rocket.
+ * "compare_info" are the query parameters (version). They might be None.
+ * Only accepts the request if the Nightbot headers are present.
+ */
+#[get("/compare/<civ_a>/<civ_b>?<compare_info>")]
+pub fn compare_with_info(data_lock: State<RwLock<GameData>>, config: State<ChannelConfig>, civ_a: String, civ_b: String, compare_info: CompareInfo, nightbot_headers: NightbotHeaderFields) -> String {
+	let data = data_lock.read().unwrap();
+	let comparison = match config.game_version(&nightbot_headers, compare_info.version.as_ref()) {
+		Ok(version) => fetch_comparison(&data, &civ_a, &civ_b, version),
+		Err(_) => String::from("That game version does not exist.")
+	};
+	
+	util::create_response(comparison, &nightbot_headers)
+}
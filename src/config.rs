@@ -0,0 +1,100 @@
+use std::collections::HashMap;
+use std::fs;
+use std::path::Path;
+
+use serde_json;
+
+use util::{
+	self,
+	NightbotHeaderFields
+};
+use data::GameVersion;
+use template::Templates;
+
+/*
+ * Environment variable holding the path of the channel config file.
+ */
+pub const CONFIG_FILE_VAR: &'static str = "CHANNEL_CONFIG";
+
+/*
+ * Per channel settings, keyed by the Nightbot channel name.
+ * The config file looks like this:
+ * 
+ * {
+ *     "channels": {
+ *         "somechannel": {
+ *             "version": "aoc",
+ *             "templates": { "elo": "{name}: {elo} ({ladder})" }
+ *         }
+ *     }
+ * }
+ */
+#[derive(Deserialize, Default)]
+pub struct ChannelConfig {
+	#[serde(default)]
+	channels: HashMap<String, ChannelSettings>
+}
+
+#[derive(Deserialize, Default)]
+pub struct ChannelSettings {
+	pub version: Option<String>,
+	/* Answer templates overriding the default wording, keyed by template name */
+	#[serde(default)]
+	pub templates: HashMap<String, String>
+}
+
+impl ChannelConfig {
+	/*
+	 * Reads and parses the config file.
+	 * Unknown channel versions are reported and ignored, so the channel is not stuck answering that the version does not exist.
+	 */
+	pub fn load<P>(path: P) -> Result<Self, String> where P: AsRef<Path> {
+		let config = fs::read_to_string(path).map_err(|e| e.to_string())?;
+		let mut config: ChannelConfig = serde_json::from_str(&config).map_err(|e| e.to_string())?;
+		
+		for (channel, settings) in config.channels.iter_mut() {
+			let invalid = settings.version.as_ref().map_or(false, |version| GameVersion::parse(version).is_none());
+			
+			if invalid {
+				eprintln!("Ignoring unknown game version \"{}\" of channel {}", settings.version.take().unwrap_or_default(), channel);
+			}
+		}
+		
+		Ok(config)
+	}
+	
+	/*
+	 * Gets the settings of the channel the request was issued in.
+	 */
+	pub fn settings(&self, nightbot_headers: &NightbotHeaderFields) -> Option<&ChannelSettings> {
+		let params = util::parse_nightbot_user_param(&nightbot_headers.channel);
+		let channel = params.get("name")?;
+		
+		self.channels.iter()
+			.filter(|(name, _)| name.eq_ignore_ascii_case(channel))
+			.map(|(_, settings)| settings)
+			.next()
+	}
+	
+	/*
+	 * Gets the answer templates of the channel the request was issued in.
+	 */
+	pub fn templates(&self, nightbot_headers: &NightbotHeaderFields) -> Templates {
+		Templates::new(self.settings(nightbot_headers).map(|settings| &settings.templates))
+	}
+	
+	/*
+	 * Determines the game version to filter by.
+	 * A requested version overrides the channel default. None means no filter.
+	 * If the requested version is unknown, it is returned as error.
+	 */
+	pub fn game_version(&self, nightbot_headers: &NightbotHeaderFields, requested: Option<&String>) -> Result<Option<GameVersion>, String> {
+		let channel_version = self.settings(nightbot_headers).and_then(|settings| settings.version.as_ref());
+		
+		if let Some(version) = requested.or(channel_version) {
+			GameVersion::parse(version).map(Some).ok_or_else(|| version.to_string())
+		} else {
+			Ok(None)
+		}
+	}
+}
@@ -1,3 +1,19 @@
+use std::collections::HashMap;
+use std::fs;
+use std::path::Path;
+use std::fmt::{
+	self,
+	Display,
+	Formatter
+};
+use std::ops::Add;
+use std::time::Duration;
+
+use serde::{
+	de,
+	Deserialize,
+	Deserializer
+};
 use serde_json::{
 	self,
 	Value,
@@ -6,6 +22,104 @@ use serde_json::{
 
 use util;
 
+pub const BUILDING_FILE: &'static str = "buildings.json";
+pub const CIV_FILE: &'static str = "civs.json";
+pub const TECH_FILE: &'static str = "techs.json";
+pub const UNIT_FILE: &'static str = "units.json";
+pub const ALIAS_FILE: &'static str = "aliases.json";
+
+/*
+ * Armor classes attack bonuses can refer to and the words used for them in the "attack bonus" entries.
+ */
+const ARMOR_CLASSES: &[(&str, &[&str])] = &[
+	("infantry", &["infantry"]),
+	("spearmen", &["spearmen", "spearman", "spearman line"]),
+	("eagles", &["eagles", "eagle warriors"]),
+	("archers", &["archers", "archer"]),
+	("cavalry archers", &["cavalry archers"]),
+	("skirmishers", &["skirmishers", "skirms"]),
+	("hand cannoneers", &["hand cannoneers", "hand cannon"]),
+	("cavalry", &["cavalry"]),
+	("camels", &["camels"]),
+	("war elephants", &["war elephants", "elephants"]),
+	("siege", &["siege", "siege weapons", "siege wapons"]),
+	("rams", &["rams"]),
+	("ships", &["ships"]),
+	("monks", &["monks"]),
+	("unique units", &["unique units"])
+];
+
+/*
+ * Armor classes derived from words in the unit name.
+ */
+const NAME_CLASSES: &[(&str, &str)] = &[
+	("spearman", "spearmen"),
+	("pikeman", "spearmen"),
+	("halberdier", "spearmen"),
+	("kamayuk", "spearmen"),
+	("eagle", "eagles"),
+	("cavalry archer", "cavalry archers"),
+	("skirmisher", "skirmishers"),
+	("hand cannoneer", "hand cannoneers"),
+	("camel", "camels"),
+	("mameluke", "camels"),
+	("elephant", "war elephants"),
+	("ram", "rams")
+];
+
+/*
+ * Armor classes of the unique units, which can't be derived from the building producing them.
+ */
+const UNIQUE_UNIT_CLASSES: &[(&str, &[&str])] = &[
+	("Arambai", &["cavalry archers", "cavalry"]),
+	("Ballista Elephant", &["war elephants", "siege"]),
+	("Berserk", &["infantry"]),
+	("Boyar", &["cavalry"]),
+	("Camel Archer", &["cavalry archers", "camels"]),
+	("Cataphract", &["cavalry"]),
+	("Chu Ko Nu", &["archers"]),
+	("Conquistador", &["cavalry archers", "hand cannoneers"]),
+	("Elephant Archer", &["cavalry archers", "war elephants"]),
+	("Gbeto", &["infantry"]),
+	("Genoese Crossbowman", &["archers"]),
+	("Huskarl", &["infantry"]),
+	("Jaguar Warrior", &["infantry"]),
+	("Janissary", &["archers", "hand cannoneers"]),
+	("Kamayuk", &["infantry", "spearmen"]),
+	("Karambit Warrior", &["infantry"]),
+	("Longbowman", &["archers"]),
+	("Magyar Huszar", &["cavalry"]),
+	("Mameluke", &["cavalry", "camels"]),
+	("Mangudai", &["cavalry archers"]),
+	("Organ Gun", &["siege"]),
+	("Plumed Archer", &["archers"]),
+	("Rattan Archer", &["archers"]),
+	("Samurai", &["infantry"]),
+	("Shotel Warrior", &["infantry"]),
+	("Tarkan", &["cavalry"]),
+	("Teutonic Knight", &["infantry"]),
+	("Throwing Axeman", &["infantry"]),
+	("War Elephant", &["war elephants"]),
+	("War Wagon", &["cavalry archers"]),
+	("Woad Raider", &["infantry"])
+];
+
+/*
+ * Adjectives used in civ bonus keys like "Celtic allies" which don't match the civ name after shrinking.
+ */
+const CIV_ADJECTIVES: &[(&str, &str)] = &[
+	("British", "Britons"),
+	("Celtic", "Celts"),
+	("Frankish", "Franks"),
+	("Gothic", "Goths"),
+	("Hunnic", "Huns"),
+	("Incan", "Incas"),
+	("Mongolian", "Mongols"),
+	("Slavic", "Slavs"),
+	("Teutonic", "Teutons"),
+	("Turkish", "Turks")
+];
+
 /*
  * Struct holding all the static data regarding AoE 2.
  */
@@ -13,7 +127,125 @@ pub struct GameData {
 	pub buildings: Vec<Building>,
 	pub civs: Vec<Civ>,
 	pub techs: Vec<Tech>,
-	pub units: Vec<Unit>
+	pub units: Vec<Unit>,
+	pub aliases: Aliases,
+	upgrades: Vec<Upgrade>
+}
+
+/*
+ * Abbreviations and slang names used in chat, mapped to the actual names.
+ */
+#[derive(Serialize, Deserialize, Default)]
+pub struct Aliases {
+	#[serde(default)]
+	pub units: HashMap<String, String>,
+	#[serde(default)]
+	pub techs: HashMap<String, String>,
+	#[serde(default)]
+	pub buildings: HashMap<String, String>,
+	#[serde(default)]
+	pub civs: HashMap<String, String>
+}
+
+/*
+ * Reference to any unit, building or tech.
+ */
+#[derive(Clone, Copy)]
+pub enum Entity<'a> {
+	Unit(&'a Unit),
+	Building(&'a Building),
+	Tech(&'a Tech)
+}
+
+/*
+ * Tech upgrading one unit to the next one of its line.
+ * Holds indices into the unit and tech lists of the game data.
+ */
+struct Upgrade {
+	from: usize,
+	to: usize,
+	tech: usize
+}
+
+/*
+ * Unit of a unit line and the tech upgrading the previous unit to it (None for the first unit).
+ */
+pub struct LineStep<'a> {
+	pub unit: &'a Unit,
+	pub tech: Option<&'a Tech>
+}
+
+/*
+ * Stat change caused by a tech, parsed from effects like "+1/+2 armor" or "HP * 1.4".
+ */
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum Modifier {
+	HitPoints(i32),
+	HitPointFactor(f32),
+	Attack(i32),
+	Armor(i32, i32),
+	Range(i32),
+	LineOfSight(i32),
+	MovementSpeedFactor(f32)
+}
+
+/*
+ * Modifiers of a tech and the units and classes they apply to, normalized like target_words does.
+ */
+struct TechEffect {
+	targets: Vec<String>,
+	modifiers: Vec<Modifier>
+}
+
+/*
+ * Numeric stats of a unit which techs can modify.
+ */
+#[derive(Serialize, Clone, Copy, PartialEq, Debug)]
+pub struct UnitStats {
+	pub hp: Option<f32>,
+	pub attack: Option<i32>,
+	pub armor: Option<(i32, i32)>,
+	pub range: Option<f32>,
+	pub line_of_sight: Option<i32>,
+	pub movement_speed: Option<f32>
+}
+
+/*
+ * Game versions in the order they were released.
+ */
+#[derive(Serialize, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Debug)]
+pub enum GameVersion {
+	Kings,
+	Conquerors,
+	Forgotten,
+	AfricanKingdoms,
+	RiseOfTheRajas
+}
+
+/*
+ * Ages in the order they are reached.
+ * Deserialized from the age numbers "0" to "3" of the game data.
+ */
+#[derive(Serialize, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Debug)]
+pub enum Age {
+	Dark,
+	Feudal,
+	Castle,
+	Imperial
+}
+
+/*
+ * Resource cost of a unit, tech or building.
+ * Deserialized from strings like "100F 40G" or "175W".
+ */
+#[derive(Serialize, Clone, Default, PartialEq, Eq, Debug)]
+pub struct Cost {
+	pub food: u32,
+	pub wood: u32,
+	pub gold: u32,
+	pub stone: u32,
+	/* Qualifier that is not an amount, like "each time" in "400G each time" */
+	pub note: Option<String>
 }
 
 #[derive(Serialize, Deserialize)]
@@ -40,8 +272,8 @@ pub struct Building {
 	pub name: String,
 	#[serde(rename = "ver")]
 	pub game_version: String,
-	pub age: String,
-	pub cost: String,
+	pub age: Age,
+	pub cost: Cost,
 	#[serde(rename = "bt")]
 	pub building_time: String,
 	pub fr: String,//???
@@ -67,8 +299,8 @@ pub struct Tech {
 	pub type_name: String,
 	pub name: String,
 	pub game_version: String,
-	pub age: String,
-	pub cost: String,
+	pub age: Age,
+	pub cost: Cost,
 	pub extra: Option<Map<String, Value>>,
 	#[serde(rename = "for")]
 	pub for_what: String,
@@ -78,6 +310,15 @@ pub struct Tech {
 	pub not_available_to: Vec<String>
 }
 
+/*
+ * Bonus damage against units of a certain armor class or name.
+ * Parsed from the "attack bonus" entry of a unit.
+ */
+pub struct AttackBonus {
+	pub amount: i32,
+	pub target: String
+}
+
 #[derive(Serialize, Deserialize)]
 pub struct Unit {
 	#[serde(rename = "type")]
@@ -85,8 +326,8 @@ pub struct Unit {
 	pub name: String,
 	#[serde(rename = "ver")]
 	pub game_version: String,
-	pub age: String,
-	pub cost: String,
+	pub age: Age,
+	pub cost: Cost,
 	#[serde(rename = "bt")]
 	pub time: String,
 	#[serde(rename = "fr")]
@@ -115,102 +356,1574 @@ pub struct Unit {
 impl GameData {
 	/*
 	 * Parses the given JSON data into the structs above.
+	 * Fails if any of the JSON data is malformed or if there are no civs or units.
 	 */
-	pub fn new<S, T, U, V>(building_data: S, civ_data: T, tech_data: U, unit_data: V) -> Self where S: AsRef<str>, T: AsRef<str>, U: AsRef<str>, V: AsRef<str> {
-		let buildings: Vec<Building> = serde_json::from_str(building_data.as_ref()).expect("Buildings JSON");
-		let civs: Vec<Civ> = serde_json::from_str(civ_data.as_ref()).expect("Civs JSON");
-		let techs: Vec<Tech> = serde_json::from_str(tech_data.as_ref()).expect("Tech JSON");
-		let units: Vec<Unit> = serde_json::from_str(unit_data.as_ref()).expect("Unit JSON");
+	pub fn new<S, T, U, V, W>(building_data: S, civ_data: T, tech_data: U, unit_data: V, alias_data: W) -> Result<Self, String> where S: AsRef<str>, T: AsRef<str>, U: AsRef<str>, V: AsRef<str>, W: AsRef<str> {
+		let buildings: Vec<Building> = serde_json::from_str(building_data.as_ref()).map_err(|e| format!("{}: {}", BUILDING_FILE, e))?;
+		let civs: Vec<Civ> = serde_json::from_str(civ_data.as_ref()).map_err(|e| format!("{}: {}", CIV_FILE, e))?;
+		let techs: Vec<Tech> = serde_json::from_str(tech_data.as_ref()).map_err(|e| format!("{}: {}", TECH_FILE, e))?;
+		let units: Vec<Unit> = serde_json::from_str(unit_data.as_ref()).map_err(|e| format!("{}: {}", UNIT_FILE, e))?;
+		let aliases: Aliases = serde_json::from_str(alias_data.as_ref()).map_err(|e| format!("{}: {}", ALIAS_FILE, e))?;
+		
+		if civs.is_empty() || units.is_empty() {
+			return Err(String::from("There are no civs or units"));
+		}
 		
-		GameData {
+		let upgrades = find_upgrades(&units, &techs);
+		let data = GameData {
 			buildings: buildings,
 			civs: civs,
 			techs: techs,
-			units: units
+			units: units,
+			aliases: aliases,
+			upgrades: upgrades
+		};
+		
+		for error in data.validate() {
+			eprintln!("Invalid game data: {}", error);
 		}
+		
+		Ok(data)
+	}
+	
+	/*
+	 * Reads the JSON files from the given directory and parses them.
+	 */
+	pub fn load<P>(dir: P) -> Result<Self, String> where P: AsRef<Path> {
+		let dir = dir.as_ref();
+		let read = |file_name: &str| fs::read_to_string(dir.join(file_name)).map_err(|e| format!("{}: {}", file_name, e));
+		
+		GameData::new(read(BUILDING_FILE)?, read(CIV_FILE)?, read(TECH_FILE)?, read(UNIT_FILE)?, read(ALIAS_FILE)?)
+	}
+	
+	/*
+	 * Checks that the numeric fields of all entities can be parsed and that no alias names both a unit, building or tech.
+	 * Returns a description for every problem found.
+	 */
+	pub fn validate(&self) -> Vec<String> {
+		let mut errors = Vec::new();
+		
+		for unit in &self.units {
+			let checks = [
+				("hp", &unit.hp, unit.hp().is_some()),
+				("attack", &unit.attack, unit.attack == "-" || unit.attack().is_some()),
+				("armor", &unit.armor, unit.armor().is_some()),
+				("build time", &unit.time, unit.build_time().is_some()),
+				("range", &unit.range, parse_range(&unit.range).is_some()),
+				("attack speed", &unit.attack_speed, unit.attack_speed == "-" || unit.attack_speed().is_some()),
+				("movement speed", &unit.movement_speed, unit.movement_speed().is_some()),
+				("line of sight", &unit.line_of_sight, unit.line_of_sight().is_some())
+			];
+			
+			for (field, raw, valid) in checks.iter() {
+				if !valid {
+					errors.push(format!("unit {} has invalid {} \"{}\"", unit.name, field, raw));
+				}
+			}
+		}
+		
+		for building in &self.buildings {
+			let checks = [
+				("hp", &building.hp, building.hp().is_some()),
+				("armor", &building.armor, building.armor().is_some()),
+				("build time", &building.building_time, building.build_time().is_some()),
+				("range", &building.range, parse_range(&building.range).is_some()),
+				("line of sight", &building.line_of_sight, building.line_of_sight().is_some())
+			];
+			
+			for (field, raw, valid) in checks.iter() {
+				if !valid {
+					errors.push(format!("building {} has invalid {} \"{}\"", building.name, field, raw));
+				}
+			}
+		}
+		
+		for tech in &self.techs {
+			if tech.research_time().is_none() {
+				errors.push(format!("tech {} has invalid research time \"{}\"", tech.name, tech.time));
+			}
+		}
+		
+		/* entity_by_name checks the alias tables in order, so an alias in several of them would silently pick one */
+		let alias_tables = [("unit", &self.aliases.units), ("building", &self.aliases.buildings), ("tech", &self.aliases.techs)];
+		
+		for (i, (kind, aliases)) in alias_tables.iter().enumerate() {
+			for (other_kind, other_aliases) in &alias_tables[i + 1 ..] {
+				let mut shared = aliases.keys()
+					.filter(|alias| other_aliases.keys().any(|other| util::shrink(other).eq_ignore_ascii_case(&util::shrink(alias))))
+					.collect::<Vec<_>>();
+				
+				shared.sort();
+				
+				for alias in shared {
+					errors.push(format!("alias {} is used for a {} and a {}", alias, kind, other_kind));
+				}
+			}
+		}
+		
+		errors
+	}
+	
+	/*
+	 * Gets a civ by name.
+	 * Aliases are resolved first. Misspelled or shortened names are accepted if they are close to exactly one civ.
+	 */
+	pub fn civ_by_name<S>(&self, name: S) -> Option<&Civ> where S: AsRef<str> {
+		let name = resolve_alias(&self.aliases.civs, name.as_ref());
+		
+		best_match(self.civs.iter().map(|c| (c.name.as_str(), c)), name)
 	}
 	
 	/*
 	 * Gets a tech by name.
+	 * Aliases are resolved first. Misspelled or shortened names are accepted if they are close to exactly one tech.
 	 */
 	pub fn tech_by_name<S>(&self, name: S) -> Option<&Tech> where S: AsRef<str> {
-		let name = name.as_ref();
+		let name = resolve_alias(&self.aliases.techs, name.as_ref());
 		
-		self.techs.iter().filter(|t| util::shrink(&t.name).eq_ignore_ascii_case(&util::shrink(name))).next()
+		best_match(self.techs.iter().map(|t| (t.name.as_str(), t)), name)
 	}
 	
 	/*
 	 * Gets a unit by name.
+	 * Aliases are resolved first. Misspelled or shortened names are accepted if they are close to exactly one unit.
 	 */
 	pub fn unit_by_name<S>(&self, name: S) -> Option<&Unit> where S: AsRef<str> {
-		let name = name.as_ref();
+		let name = resolve_alias(&self.aliases.units, name.as_ref());
 		
-		self.units.iter().filter(|u| util::shrink(&u.name).eq_ignore_ascii_case(&util::shrink(name))).next()
+		best_match(self.units.iter().map(|u| (u.name.as_str(), u)), name)
 	}
 	
 	/*
 	 * Gets a building by name.
+	 * Aliases are resolved first. Misspelled or shortened names are accepted if they are close to exactly one building.
 	 */
 	pub fn building_by_name<S>(&self, name: S) -> Option<&Building> where S: AsRef<str> {
+		let name = resolve_alias(&self.aliases.buildings, name.as_ref());
+		
+		best_match(self.buildings.iter().map(|b| (b.name.as_str(), b)), name)
+	}
+	
+	/*
+	 * Gets a unit, building or tech by name.
+	 * If a unit and a tech share the same name (e.g. upgrades), units are preferred over buildings and buildings over techs.
+	 */
+	pub fn entity_by_name<S>(&self, name: S) -> Option<Entity> where S: AsRef<str> {
 		let name = name.as_ref();
+		let alias_tables = [&self.aliases.units, &self.aliases.buildings, &self.aliases.techs];
+		let alias = alias_tables.iter()
+			.map(|aliases| resolve_alias(aliases, name))
+			.filter(|resolved| *resolved != name)
+			.next();
 		
-		self.buildings.iter().filter(|b| util::shrink(&b.name).eq_ignore_ascii_case(&util::shrink(name))).next()
+		best_match(self.entities().map(|e| (e.name(), e)), alias.unwrap_or(name))
 	}
-}
-
-impl Tech {
+	
 	/*
-	 * Checks if the tech is available to the specified civ.
+	 * Creates the "Did you mean ...?" prefix if the found name differs from what the user typed.
+	 * Aliases of the found name don't count as different.
 	 */
-	pub fn available_to<S>(&self, civ: S) -> Option<(bool, String)> where S: AsRef<str> {
-		let civ = civ.as_ref();
-		let civ_name = self.available_to.iter().filter(|c| util::shrink(&c).eq_ignore_ascii_case(&util::shrink(civ))).next();
-		let civ_name_2 = self.not_available_to.iter().filter(|c| util::shrink(&c).eq_ignore_ascii_case(&util::shrink(civ))).next();
+	pub fn correction(&self, query: &str, name: &str) -> String {
+		let alias_tables = [&self.aliases.units, &self.aliases.buildings, &self.aliases.techs, &self.aliases.civs];
+		let is_alias = alias_tables.iter()
+			.any(|aliases| resolve_alias(aliases, query) == name);
 		
-		if let Some(civ_name) = civ_name {
-			Some((true, civ_name.to_string()))
-		} else if let Some(civ_name) = civ_name_2 {
-			Some((false, civ_name.to_string()))
+		if is_alias {
+			String::new()
 		} else {
-			None
+			util::correction(query, name)
+		}
+	}
+	
+	/*
+	 * Returns the whole line of the given unit, starting with the unit that can be trained without upgrades.
+	 */
+	pub fn unit_line<'a>(&'a self, unit: &'a Unit) -> Vec<LineStep<'a>> {
+		let upgrade_to = |unit: &Unit| self.upgrades.iter().find(|upgrade| self.units[upgrade.to].name == unit.name);
+		let upgrade_from = |unit: &Unit| self.upgrades.iter().find(|upgrade| self.units[upgrade.from].name == unit.name);
+		let mut first = unit;
+		
+		/* The number of upgrades limits the walk in case the data contains a cycle */
+		for _ in 0..self.upgrades.len() {
+			if let Some(upgrade) = upgrade_to(first) {
+				first = &self.units[upgrade.from];
+			} else {
+				break;
+			}
+		}
+		
+		let mut line = vec![LineStep { unit: first, tech: None }];
+		
+		for _ in 0..self.upgrades.len() {
+			let upgrade = if let Some(upgrade) = upgrade_from(line[line.len() - 1].unit) {
+				upgrade
+			} else {
+				break;
+			};
+			
+			line.push(LineStep { unit: &self.units[upgrade.to], tech: Some(&self.techs[upgrade.tech]) });
+		}
+		
+		line
+	}
+	
+	/*
+	 * Collects all techs needed to fully upgrade the given unit.
+	 * These are the upgrades of its line up to the unit and all techs whose targets include the unit.
+	 * Unique techs are only included if a civ is given which can research them.
+	 */
+	pub fn unit_upgrades<'a>(&'a self, unit: &'a Unit, civ: Option<&Civ>) -> Vec<&'a Tech> {
+		let line = self.unit_line(unit);
+		let mut techs = line.iter()
+			.take_while(|step| step.unit.name != unit.name)
+			.chain(line.iter().filter(|step| step.unit.name == unit.name).take(1))
+			.filter_map(|step| step.tech)
+			.collect::<Vec<_>>();
+		
+		techs.extend(self.tech_modifiers(unit, civ).into_iter().map(|(tech, _)| tech));
+		techs
+	}
+	
+	/*
+	 * Calculates the stats of the given unit with all techs of unit_upgrades researched.
+	 * If a civ or game version is given, only the techs the civ can research in that version are applied.
+	 */
+	pub fn upgraded_stats(&self, unit: &Unit, civ: Option<&Civ>, version: Option<GameVersion>) -> UnitStats {
+		let mut stats = unit.stats();
+		let modifiers = self.tech_modifiers(unit, civ)
+			.into_iter()
+			.filter(|&(tech, _)| version.map_or(true, |version| version.includes(tech.version())))
+			.filter(|&(tech, _)| civ.and_then(|civ| tech.available_to(&civ.name)).map_or(true, |(available, _)| available))
+			.flat_map(|(_, modifiers)| modifiers);
+		
+		for modifier in modifiers {
+			stats.apply(modifier);
+		}
+		
+		stats
+	}
+	
+	/*
+	 * Finds the techs (except unit line upgrades) whose targets include the given unit, together with their modifiers for it.
+	 * Unique techs are only included if a civ is given which can research them.
+	 */
+	fn tech_modifiers<'a>(&'a self, unit: &Unit, civ: Option<&Civ>) -> Vec<(&'a Tech, Vec<Modifier>)> {
+		let mut vocabulary = ARMOR_CLASSES.iter()
+			.flat_map(|&(_, words)| words.iter().map(|word| target_words(word)))
+			.chain(self.units.iter().map(|unit| target_words(&unit.name)))
+			.collect::<Vec<_>>();
+		let unit_words = unit.tech_classes()
+			.into_iter()
+			.flat_map(|class| ARMOR_CLASSES.iter().filter(move |&&(name, _)| name == class))
+			.flat_map(|&(_, words)| words.iter().map(|word| target_words(word)))
+			.chain(self.unit_line(unit).iter().map(|step| target_words(&step.unit.name)))
+			.collect::<Vec<_>>();
+		let mut techs = Vec::new();
+		
+		/* Longer words come first, so "cavalry archers" is not mistaken for "cavalry" */
+		vocabulary.sort_by(|a, b| b.len().cmp(&a.len()));
+		
+		for (i, tech) in self.techs.iter().enumerate() {
+			if self.upgrades.iter().any(|upgrade| upgrade.tech == i) {
+				continue;
+			}
+			
+			if Entity::Tech(tech).is_unique() && civ.and_then(|civ| tech.available_to(&civ.name)).map_or(true, |(available, _)| !available) {
+				continue;
+			}
+			
+			let effects = tech.effects()
+				.into_iter()
+				.filter(|effect| effect.targets.iter().any(|target| {
+					vocabulary.iter()
+						.find(|word| target == *word || target.starts_with(&format!("{} ", word)))
+						.map_or(false, |word| unit_words.contains(word))
+				}))
+				.collect::<Vec<_>>();
+			
+			if !effects.is_empty() {
+				techs.push((tech, effects.into_iter().flat_map(|effect| effect.modifiers).collect()));
+			}
 		}
+		
+		techs
+	}
+	
+	/*
+	 * Iterates over all units, buildings and techs.
+	 */
+	pub fn entities<'a>(&'a self) -> impl Iterator<Item = Entity<'a>> + 'a {
+		let units = self.units.iter().map(Entity::Unit);
+		let buildings = self.buildings.iter().map(Entity::Building);
+		let techs = self.techs.iter().map(Entity::Tech);
+		
+		units.chain(buildings).chain(techs)
+	}
+	
+	/*
+	 * Lists up to three civ names similar to the given name.
+	 */
+	pub fn civ_suggestions<S>(&self, name: S) -> Vec<&str> where S: AsRef<str> {
+		suggestions(self.civs.iter().map(|c| c.name.as_str()), name.as_ref())
+	}
+	
+	/*
+	 * Lists up to three tech names similar to the given name.
+	 */
+	pub fn tech_suggestions<S>(&self, name: S) -> Vec<&str> where S: AsRef<str> {
+		suggestions(self.techs.iter().map(|t| t.name.as_str()), name.as_ref())
+	}
+	
+	/*
+	 * Lists up to three unit names similar to the given name.
+	 */
+	pub fn unit_suggestions<S>(&self, name: S) -> Vec<&str> where S: AsRef<str> {
+		suggestions(self.units.iter().map(|u| u.name.as_str()), name.as_ref())
+	}
+	
+	/*
+	 * Lists up to three building names similar to the given name.
+	 */
+	pub fn building_suggestions<S>(&self, name: S) -> Vec<&str> where S: AsRef<str> {
+		suggestions(self.buildings.iter().map(|b| b.name.as_str()), name.as_ref())
+	}
+	
+	/*
+	 * Lists up to three unit, building or tech names similar to the given name.
+	 */
+	pub fn entity_suggestions<S>(&self, name: S) -> Vec<&str> where S: AsRef<str> {
+		suggestions(self.entities().map(|e| e.name()), name.as_ref())
 	}
 }
 
-impl Unit {
+impl<'a> Entity<'a> {
+	pub fn name(&self) -> &'a str {
+		match *self {
+			Entity::Unit(unit) => &unit.name,
+			Entity::Building(building) => &building.name,
+			Entity::Tech(tech) => &tech.name
+		}
+	}
+	
+	pub fn version(&self) -> Option<GameVersion> {
+		match *self {
+			Entity::Unit(unit) => unit.version(),
+			Entity::Building(building) => building.version(),
+			Entity::Tech(tech) => tech.version()
+		}
+	}
+	
+	pub fn age(&self) -> Age {
+		match *self {
+			Entity::Unit(unit) => unit.age,
+			Entity::Building(building) => building.age,
+			Entity::Tech(tech) => tech.age
+		}
+	}
+	
 	/*
-	 * Checks if the unit is available to the specified civ.
+	 * Checks if the entity is a unique unit or tech (or an upgrade of one).
+	 */
+	pub fn is_unique(&self) -> bool {
+		match *self {
+			Entity::Unit(unit) => unit.type_name == "Unique",
+			Entity::Building(_) => false,
+			Entity::Tech(tech) => tech.type_name.starts_with("Unique")
+		}
+	}
+	
+	/*
+	 * Checks if the entity is available to the specified civ.
 	 */
 	pub fn available_to<S>(&self, civ: S) -> Option<(bool, String)> where S: AsRef<str> {
-		let civ = civ.as_ref();
-		let civ_name = self.available_to.iter().filter(|c| util::shrink(&c).eq_ignore_ascii_case(&util::shrink(civ))).next();
-		let civ_name_2 = self.not_available_to.iter().filter(|c| util::shrink(&c).eq_ignore_ascii_case(&util::shrink(civ))).next();
+		match *self {
+			Entity::Unit(unit) => unit.available_to(civ),
+			Entity::Building(building) => building.available_to(civ),
+			Entity::Tech(tech) => tech.available_to(civ)
+		}
+	}
+}
+
+impl GameVersion {
+	/*
+	 * Maps the "ver" field of the game data to the game version.
+	 */
+	pub fn from_code<S>(code: S) -> Option<GameVersion> where S: AsRef<str> {
+		match code.as_ref() {
+			"k" => Some(GameVersion::Kings),
+			"c" => Some(GameVersion::Conquerors),
+			"f" => Some(GameVersion::Forgotten),
+			"a" => Some(GameVersion::AfricanKingdoms),
+			"r" => Some(GameVersion::RiseOfTheRajas),
+			_ => None
+		}
+	}
+	
+	/*
+	 * Parses a game version passed by the user, either the code or a common name.
+	 */
+	pub fn parse<S>(name: S) -> Option<GameVersion> where S: AsRef<str> {
+		match util::shrink(name.as_ref()).to_lowercase().as_str() {
+			"k" | "aok" | "king" | "ageofking" | "theageofking" => Some(GameVersion::Kings),
+			"c" | "aoc" | "conqueror" | "theconqueror" => Some(GameVersion::Conquerors),
+			"f" | "fe" | "forgotten" | "theforgotten" => Some(GameVersion::Forgotten),
+			"a" | "ak" | "african" | "africankingdom" | "theafricankingdom" => Some(GameVersion::AfricanKingdoms),
+			"r" | "rotr" | "raja" | "riseoftheraja" => Some(GameVersion::RiseOfTheRajas),
+			_ => None
+		}
+	}
+	
+	pub fn name(&self) -> &'static str {
+		match *self {
+			GameVersion::Kings => "The Age of Kings",
+			GameVersion::Conquerors => "The Conquerors",
+			GameVersion::Forgotten => "The Forgotten",
+			GameVersion::AfricanKingdoms => "The African Kingdoms",
+			GameVersion::RiseOfTheRajas => "Rise of the Rajas"
+		}
+	}
+	
+	/*
+	 * Checks if something introduced in the given version is part of this version.
+	 * Things with an unknown version are always included.
+	 */
+	pub fn includes(&self, introduced: Option<GameVersion>) -> bool {
+		introduced.map(|introduced| introduced <= *self).unwrap_or(true)
+	}
+}
+
+impl Age {
+	/*
+	 * Maps the "age" field of the game data to the age.
+	 */
+	pub fn from_code<S>(code: S) -> Option<Age> where S: AsRef<str> {
+		match code.as_ref().trim() {
+			"0" => Some(Age::Dark),
+			"1" => Some(Age::Feudal),
+			"2" => Some(Age::Castle),
+			"3" => Some(Age::Imperial),
+			_ => None
+		}
+	}
+	
+	/*
+	 * Parses an age from a name like "feudal", "Castle Age" or "imp" or its number.
+	 */
+	pub fn parse<S>(name: S) -> Option<Age> where S: AsRef<str> {
+		let name = util::shrink(name.as_ref()).to_lowercase();
+		let name = name.trim_right_matches("age");
 		
-		if let Some(civ_name) = civ_name {
-			Some((true, civ_name.to_string()))
-		} else if let Some(civ_name) = civ_name_2 {
-			Some((false, civ_name.to_string()))
-		} else {
-			None
+		match name {
+			"dark" | "d" => Some(Age::Dark),
+			"feudal" | "feud" | "f" => Some(Age::Feudal),
+			"castle" | "c" => Some(Age::Castle),
+			"imperial" | "imp" | "i" => Some(Age::Imperial),
+			_ => Age::from_code(name)
 		}
 	}
 }
 
-impl Building {
+impl Display for Age {
+	fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+		match *self {
+			Age::Dark => write!(f, "dark age"),
+			Age::Feudal => write!(f, "feudal age"),
+			Age::Castle => write!(f, "castle age"),
+			Age::Imperial => write!(f, "imperial age")
+		}
+	}
+}
+
+impl<'de> Deserialize<'de> for Age {
+	fn deserialize<D>(deserializer: D) -> Result<Self, D::Error> where D: Deserializer<'de> {
+		let s = String::deserialize(deserializer)?;
+		
+		Age::from_code(&s).ok_or_else(|| de::Error::custom(format!("invalid age \"{}\"", s)))
+	}
+}
+
+impl Cost {
 	/*
-	 * Checks if the building is available to the specified civ.
+	 * Parses a cost string like "100F 40G".
+	 * Text that is not an amount followed by F, W, G or S is kept as the note, so "400G each time" keeps "each time".
+	 * Returns None if neither an amount nor "free" is found.
 	 */
-	pub fn available_to<S>(&self, civ: S) -> Option<(bool, String)> where S: AsRef<str> {
-		let civ = civ.as_ref();
-		let civ_name = self.available_to.iter().filter(|c| util::shrink(&c).eq_ignore_ascii_case(&util::shrink(civ))).next();
-		let civ_name_2 = self.not_available_to.iter().filter(|c| util::shrink(&c).eq_ignore_ascii_case(&util::shrink(civ))).next();
+	pub fn parse<S>(s: S) -> Option<Cost> where S: AsRef<str> {
+		let s = s.as_ref();
+		let mut cost = Cost::default();
+		let mut found = false;
+		let mut rest = Vec::new();
 		
-		if let Some(civ_name) = civ_name {
-			Some((true, civ_name.to_string()))
-		} else if let Some(civ_name) = civ_name_2 {
-			Some((false, civ_name.to_string()))
+		for word in s.split_whitespace() {
+			let digits = word.chars().take_while(char::is_ascii_digit).collect::<String>();
+			let resource = word[digits.len()..].chars().next();
+			
+			if word.trim_matches(|c: char| !c.is_alphanumeric()).eq_ignore_ascii_case("free") {
+				found = true;
+				
+				continue;
+			}
+			
+			if let (Ok(amount), Some(resource)) = (digits.parse::<u32>(), resource) {
+				let slot = match resource {
+					'F' => Some(&mut cost.food),
+					'W' => Some(&mut cost.wood),
+					'G' => Some(&mut cost.gold),
+					'S' => Some(&mut cost.stone),
+					_ => None
+				};
+				
+				if let Some(slot) = slot {
+					*slot += amount;
+					found = true;
+					
+					/* Text attached to the amount, like "/enemy" in "200G/enemy villager" */
+					let attached = &word[digits.len() + 1..];
+					
+					if !attached.is_empty() {
+						rest.push(attached);
+					}
+					
+					continue;
+				}
+			}
+			
+			rest.push(word);
+		}
+		
+		if !rest.is_empty() {
+			cost.note = Some(rest.join(" "));
+		}
+		
+		if found {
+			Some(cost)
 		} else {
 			None
 		}
 	}
+	
+	/*
+	 * Sum of all resources.
+	 */
+	pub fn total(&self) -> u32 {
+		self.food + self.wood + self.gold + self.stone
+	}
+	
+	pub fn is_free(&self) -> bool {
+		self.total() == 0
+	}
+	
+	/*
+	 * Applies a discount of the given percentage, rounded like the game does.
+	 */
+	pub fn discounted(&self, percent: u32) -> Cost {
+		let factor = 100 - percent.min(100);
+		let discount = |amount: u32| (amount * factor + 50) / 100;
+		
+		Cost {
+			food: discount(self.food),
+			wood: discount(self.wood),
+			gold: discount(self.gold),
+			stone: discount(self.stone),
+			note: self.note.clone()
+		}
+	}
+}
+
+impl Add for Cost {
+	type Output = Cost;
+	
+	fn add(self, other: Cost) -> Cost {
+		let note = match (self.note, other.note) {
+			(Some(note), Some(other_note)) => Some(format!("{}, {}", note, other_note)),
+			(note, other_note) => note.or(other_note)
+		};
+		
+		Cost {
+			food: self.food + other.food,
+			wood: self.wood + other.wood,
+			gold: self.gold + other.gold,
+			stone: self.stone + other.stone,
+			note: note
+		}
+	}
+}
+
+impl Display for Cost {
+	fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+		let amounts = if self.is_free() {
+			String::from("nothing")
+		} else {
+			[(self.food, 'F'), (self.wood, 'W'), (self.gold, 'G'), (self.stone, 'S')].iter()
+				.filter(|(amount, _)| *amount > 0)
+				.map(|(amount, resource)| format!("{}{}", amount, resource))
+				.collect::<Vec<_>>()
+				.join(" ")
+		};
+		
+		match self.note {
+			Some(ref note) if note.starts_with(char::is_alphanumeric) => write!(f, "{} {}", amounts, note),
+			Some(ref note) => write!(f, "{}{}", amounts, note),
+			None => write!(f, "{}", amounts)
+		}
+	}
+}
+
+impl<'de> Deserialize<'de> for Cost {
+	fn deserialize<D>(deserializer: D) -> Result<Self, D::Error> where D: Deserializer<'de> {
+		let s = String::deserialize(deserializer)?;
+		
+		Cost::parse(&s).ok_or_else(|| de::Error::custom(format!("invalid cost \"{}\"", s)))
+	}
+}
+
+impl Civ {
+	pub fn version(&self) -> Option<GameVersion> {
+		GameVersion::from_code(&self.game_version)
+	}
+	
+	/*
+	 * Returns the civ boni as a list of plain text entries.
+	 */
+	pub fn boni(&self) -> Vec<String> {
+		util::strip_html_list(&self.boni)
+	}
+	
+	/*
+	 * Returns the names of the unique techs without the age annotation.
+	 */
+	pub fn unique_techs(&self) -> Vec<String> {
+		self.unique_tech.split(',')
+			.map(|tech| tech.split('(').next().unwrap_or("").trim().to_string())
+			.filter(|tech| !tech.is_empty())
+			.collect()
+	}
+	
+	/*
+	 * Returns the names of the unique units without the building annotation, the castle unit first.
+	 */
+	pub fn unique_units(&self) -> Vec<String> {
+		self.unique_unit.split(',')
+			.map(|unit| unit.split('(').next().unwrap_or("").trim().to_string())
+			.filter(|unit| !unit.is_empty())
+			.collect()
+	}
+}
+
+impl Tech {
+	pub fn version(&self) -> Option<GameVersion> {
+		GameVersion::from_code(&self.game_version)
+	}
+	
+	/*
+	 * Returns the time it takes to research the tech, without civ specific bonuses.
+	 */
+	pub fn research_time(&self) -> Option<Duration> {
+		parse_time(&self.time)
+	}
+	
+	/*
+	 * Returns the notes regarding the specified civ.
+	 */
+	pub fn civ_notes<S>(&self, civ: S) -> Vec<String> where S: AsRef<str> {
+		civ_notes(&self.civ_boni, civ)
+	}
+	
+	/*
+	 * Returns the cost for the specified civ, taking notes like "Free" or "Cost 100F (no gold cost)" into account.
+	 * If the cost depends on the age, the cost in the last age is used.
+	 */
+	pub fn civ_cost<S>(&self, civ: S) -> Cost where S: AsRef<str> {
+		own_civ_note_entries(&self.civ_boni, civ)
+			.into_iter()
+			.filter_map(|(_, note)| parse_civ_cost(note, &self.cost))
+			.next()
+			.unwrap_or_else(|| self.cost.clone())
+	}
+	
+	/*
+	 * Returns the research time for the specified civ, taking notes like "BT 0:33.3" into account.
+	 * If the time depends on the age, the time in the last age is used.
+	 */
+	pub fn civ_research_time<S>(&self, civ: S) -> Option<Duration> where S: AsRef<str> {
+		own_civ_note_entries(&self.civ_boni, civ)
+			.into_iter()
+			.filter_map(|(_, note)| parse_civ_time(note))
+			.next()
+			.or_else(|| self.research_time())
+	}
+	
+	/*
+	 * Splits the effects of the tech by the units and classes they apply to.
+	 * If present, the "extra" entries are used, which map targets to their effect.
+	 * Otherwise the "for" field is split into clauses like "Britons, foot archers +1 range, Towers +2 attack",
+	 * where the targets of a clause are the words up to the first number or operator.
+	 */
+	fn effects(&self) -> Vec<TechEffect> {
+		let split_targets = |targets: &str| {
+			targets.replace("<br />", ",")
+				.replace(" and ", ",")
+				.split(',')
+				.map(target_words)
+				.filter(|target| !target.is_empty())
+				.collect::<Vec<_>>()
+		};
+		let effect_start = |clause: &str| clause.find(|c: char| c == '+' || c == '*' || c.is_ascii_digit()).unwrap_or(clause.len());
+		
+		if let Some(ref extra) = self.extra {
+			return extra.iter()
+				.map(|(targets, effect)| TechEffect {
+					targets: split_targets(&targets[..effect_start(targets.as_str())]),
+					modifiers: effect.as_str().map(parse_modifiers).unwrap_or_else(Vec::new)
+				})
+				.collect();
+		}
+		
+		let mut effects: Vec<TechEffect> = Vec::new();
+		
+		for clause in self.for_what.split(',') {
+			let start = effect_start(clause);
+			let targets = split_targets(&clause[..start]);
+			let modifiers = parse_modifiers(clause);
+			let starts_new_effect = effects.last().map_or(true, |effect| !effect.modifiers.is_empty() && !targets.is_empty());
+			
+			if starts_new_effect {
+				effects.push(TechEffect { targets: targets, modifiers: modifiers });
+			} else if let Some(effect) = effects.last_mut() {
+				effect.targets.extend(targets);
+				effect.modifiers.extend(modifiers);
+			}
+		}
+		
+		effects
+	}
+	
+	/*
+	 * Checks if the tech is available to the specified civ.
+	 */
+	pub fn available_to<S>(&self, civ: S) -> Option<(bool, String)> where S: AsRef<str> {
+		let civ = civ.as_ref();
+		let civ_name = self.available_to.iter().filter(|c| util::shrink(&c).eq_ignore_ascii_case(&util::shrink(civ))).next();
+		let civ_name_2 = self.not_available_to.iter().filter(|c| util::shrink(&c).eq_ignore_ascii_case(&util::shrink(civ))).next();
+		
+		if let Some(civ_name) = civ_name {
+			Some((true, civ_name.to_string()))
+		} else if let Some(civ_name) = civ_name_2 {
+			Some((false, civ_name.to_string()))
+		} else {
+			None
+		}
+	}
+}
+
+impl UnitStats {
+	/*
+	 * Applies a modifier. Stats the unit doesn't have (like the range of melee units) stay unchanged.
+	 */
+	pub fn apply(&mut self, modifier: Modifier) {
+		match modifier {
+			Modifier::HitPoints(amount) => self.hp = self.hp.map(|hp| hp + amount as f32),
+			Modifier::HitPointFactor(factor) => self.hp = self.hp.map(|hp| hp * factor),
+			Modifier::Attack(amount) => self.attack = self.attack.map(|attack| attack + amount),
+			Modifier::Armor(melee, pierce) => self.armor = self.armor.map(|(m, p)| (m + melee, p + pierce)),
+			Modifier::Range(amount) => self.range = self.range.map(|range| range + amount as f32),
+			Modifier::LineOfSight(amount) => self.line_of_sight = self.line_of_sight.map(|los| los + amount),
+			Modifier::MovementSpeedFactor(factor) => self.movement_speed = self.movement_speed.map(|speed| speed * factor)
+		}
+	}
+}
+
+impl AttackBonus {
+	/*
+	 * Checks if the bonus applies to the specified unit, either by armor class or by name.
+	 * Targets like "archers/hand cannon" or "walls and gates" apply if any of the alternatives does.
+	 */
+	pub fn applies_to(&self, unit: &Unit) -> bool {
+		let classes = unit.classes();
+		let target = self.target.replace(" and ", "/").replace('&', "/");
+		
+		target.split('/')
+			.map(|alternative| {
+				let alternative = alternative.trim();
+				let alternative = alternative.trim_left_matches("vs ").trim_left_matches("against ");
+				
+				util::shrink(alternative).to_lowercase()
+			})
+			.any(|alternative| {
+				let is_class = ARMOR_CLASSES.iter()
+					.filter(|(class, _)| classes.contains(class))
+					.flat_map(|(_, words)| words.iter())
+					.any(|word| util::shrink(word) == alternative);
+				
+				is_class || util::shrink(&unit.name).to_lowercase() == alternative
+			})
+	}
+}
+
+impl Unit {
+	pub fn version(&self) -> Option<GameVersion> {
+		GameVersion::from_code(&self.game_version)
+	}
+	
+	/*
+	 * Returns the hit points of the unit without any upgrades.
+	 */
+	pub fn hp(&self) -> Option<u32> {
+		self.hp.trim().parse().ok()
+	}
+	
+	/*
+	 * Returns None for units without an attack.
+	 */
+	pub fn attack(&self) -> Option<u32> {
+		self.attack.trim().parse().ok()
+	}
+	
+	/*
+	 * Returns (melee armor, pierce armor).
+	 */
+	pub fn armor(&self) -> Option<(i32, i32)> {
+		parse_armor(&self.armor)
+	}
+	
+	pub fn melee_armor(&self) -> Option<i32> {
+		self.armor().map(|(melee, _)| melee)
+	}
+	
+	pub fn pierce_armor(&self) -> Option<i32> {
+		self.armor().map(|(_, pierce)| pierce)
+	}
+	
+	/*
+	 * Returns the time it takes to train the unit.
+	 */
+	pub fn build_time(&self) -> Option<Duration> {
+		parse_time(&self.time)
+	}
+	
+	/*
+	 * Returns the maximum range or None for melee units.
+	 */
+	pub fn range(&self) -> Option<f32> {
+		parse_range(&self.range).and_then(|range| range)
+	}
+	
+	/*
+	 * Returns the seconds between two attacks or None for units without an attack.
+	 */
+	pub fn attack_speed(&self) -> Option<f32> {
+		self.attack_speed.trim().parse().ok()
+	}
+	
+	/*
+	 * Returns the tiles per second the unit moves.
+	 * Units which can only move when packed, like "- (0.8)", return the packed speed.
+	 */
+	pub fn movement_speed(&self) -> Option<f32> {
+		let speed = self.movement_speed.trim();
+		
+		if speed.starts_with('-') {
+			speed[1..].trim().trim_matches(|c: char| c == '(' || c == ')').parse().ok()
+		} else {
+			leading_number(speed)
+		}
+	}
+	
+	/*
+	 * Returns the line of sight in tiles.
+	 */
+	pub fn line_of_sight(&self) -> Option<u32> {
+		self.line_of_sight.trim().parse().ok()
+	}
+	
+	/*
+	 * Returns the numeric stats of the unit without any upgrades.
+	 */
+	pub fn stats(&self) -> UnitStats {
+		UnitStats {
+			hp: self.hp().map(|hp| hp as f32),
+			attack: self.attack().map(|attack| attack as i32),
+			armor: self.armor(),
+			range: self.range(),
+			line_of_sight: self.line_of_sight().map(|los| los as i32),
+			movement_speed: self.movement_speed()
+		}
+	}
+	
+	/*
+	 * Returns the armor classes of the unit.
+	 * They are derived from the producing building and the unit name.
+	 */
+	pub fn classes(&self) -> Vec<&'static str> {
+		let mut classes = match self.type_name.as_str() {
+			"Barracks" => vec!["infantry"],
+			"Archery Range" => vec!["archers"],
+			"Stable" => vec!["cavalry"],
+			"Siege Workshop" | "Castle" => vec!["siege"],
+			"Dock" => vec!["ships"],
+			"Monastery" => vec!["monks"],
+			"Unique" => vec!["unique units"],
+			_ => Vec::new()
+		};
+		let name = format!(" {} ", self.name.to_lowercase());
+		let base_name = self.name.trim_left_matches("Elite ");
+		
+		for (word, class) in NAME_CLASSES {
+			if name.contains(&format!(" {}", word)) && !classes.contains(class) {
+				classes.push(*class);
+			}
+		}
+		
+		for (unique_unit, unique_classes) in UNIQUE_UNIT_CLASSES {
+			if *unique_unit == base_name {
+				for class in unique_classes.iter() {
+					if !classes.contains(class) {
+						classes.push(*class);
+					}
+				}
+			}
+		}
+		
+		classes
+	}
+	
+	/*
+	 * Returns the classes techs can target the unit by.
+	 * Gunpowder units share the archer armor classes, but only get the archer armor techs, which name them explicitly.
+	 * Ranged units get the archer armor line and no cavalry techs besides the ones naming cavalry archers.
+	 */
+	pub fn tech_classes(&self) -> Vec<&'static str> {
+		let classes = self.classes();
+		let gunpowder = classes.contains(&"hand cannoneers");
+		let ranged = classes.contains(&"archers") || classes.contains(&"cavalry archers");
+		
+		classes.into_iter()
+			.filter(|class| !(gunpowder && (*class == "archers" || *class == "cavalry archers")))
+			.filter(|class| !(ranged && *class == "cavalry"))
+			.collect()
+	}
+	
+	/*
+	 * Parses the "attack bonus" entry, e.g. "+15 cavalry, +12 camels, +9 vs ships".
+	 * Conditional bonuses ("with siege engineers") and notes in parentheses are skipped.
+	 */
+	pub fn attack_bonuses(&self) -> Vec<AttackBonus> {
+		let raw = self.extra.as_ref()
+			.and_then(|extra| extra.get("attack bonus"))
+			.and_then(Value::as_str)
+			.unwrap_or("");
+		let text = strip_parentheses(raw);
+		
+		text.split('+')
+			.filter_map(|bonus| {
+				let bonus = bonus.split('.').next().unwrap_or("").trim().trim_right_matches(',');
+				let mut words = bonus.splitn(2, ' ');
+				let amount = words.next()?.parse().ok()?;
+				let target = words.next()?.trim().to_lowercase();
+				
+				if target.starts_with("with ") || target.contains(" with ") {
+					None
+				} else {
+					Some(AttackBonus {
+						amount: amount,
+						target: target
+					})
+				}
+			})
+			.collect()
+	}
+	
+	/*
+	 * Returns the notes regarding the specified civ.
+	 */
+	pub fn civ_notes<S>(&self, civ: S) -> Vec<String> where S: AsRef<str> {
+		civ_notes(&self.civ_bonus, civ)
+	}
+	
+	/*
+	 * Checks if the unit is available to the specified civ.
+	 */
+	pub fn available_to<S>(&self, civ: S) -> Option<(bool, String)> where S: AsRef<str> {
+		let civ = civ.as_ref();
+		let civ_name = self.available_to.iter().filter(|c| util::shrink(&c).eq_ignore_ascii_case(&util::shrink(civ))).next();
+		let civ_name_2 = self.not_available_to.iter().filter(|c| util::shrink(&c).eq_ignore_ascii_case(&util::shrink(civ))).next();
+		
+		if let Some(civ_name) = civ_name {
+			Some((true, civ_name.to_string()))
+		} else if let Some(civ_name) = civ_name_2 {
+			Some((false, civ_name.to_string()))
+		} else {
+			None
+		}
+	}
+}
+
+impl Building {
+	pub fn version(&self) -> Option<GameVersion> {
+		GameVersion::from_code(&self.game_version)
+	}
+	
+	pub fn hp(&self) -> Option<u32> {
+		self.hp.trim().parse().ok()
+	}
+	
+	/*
+	 * Returns (melee armor, pierce armor).
+	 */
+	pub fn armor(&self) -> Option<(i32, i32)> {
+		parse_armor(&self.armor)
+	}
+	
+	pub fn melee_armor(&self) -> Option<i32> {
+		self.armor().map(|(melee, _)| melee)
+	}
+	
+	pub fn pierce_armor(&self) -> Option<i32> {
+		self.armor().map(|(_, pierce)| pierce)
+	}
+	
+	pub fn build_time(&self) -> Option<Duration> {
+		parse_time(&self.building_time)
+	}
+	
+	/*
+	 * Returns the maximum range or None for buildings that don't shoot.
+	 */
+	pub fn range(&self) -> Option<f32> {
+		parse_range(&self.range).and_then(|range| range)
+	}
+	
+	pub fn line_of_sight(&self) -> Option<u32> {
+		self.line_of_sight.trim().parse().ok()
+	}
+	
+	/*
+	 * Returns the notes regarding the specified civ.
+	 */
+	pub fn civ_notes<S>(&self, civ: S) -> Vec<String> where S: AsRef<str> {
+		civ_notes(&self.civ_bonus, civ)
+	}
+	
+	/*
+	 * Checks if the building is available to the specified civ.
+	 */
+	pub fn available_to<S>(&self, civ: S) -> Option<(bool, String)> where S: AsRef<str> {
+		let civ = civ.as_ref();
+		let civ_name = self.available_to.iter().filter(|c| util::shrink(&c).eq_ignore_ascii_case(&util::shrink(civ))).next();
+		let civ_name_2 = self.not_available_to.iter().filter(|c| util::shrink(&c).eq_ignore_ascii_case(&util::shrink(civ))).next();
+		
+		if let Some(civ_name) = civ_name {
+			Some((true, civ_name.to_string()))
+		} else if let Some(civ_name) = civ_name_2 {
+			Some((false, civ_name.to_string()))
+		} else {
+			None
+		}
+	}
+}
+
+/*
+ * Finds the techs upgrading one unit to another one.
+ * The "for" field of such a tech names the upgraded unit (only the first one if several are listed)
+ * and the tech is named after the resulting unit.
+ */
+fn find_upgrades(units: &[Unit], techs: &[Tech]) -> Vec<Upgrade> {
+	let shrunk_names = units.iter().map(|unit| util::shrink(&unit.name).to_lowercase()).collect::<Vec<_>>();
+	let find_unit = |name: &str| {
+		let name = util::shrink(name).to_lowercase();
+		
+		shrunk_names.iter().position(|unit_name| *unit_name == name)
+	};
+	/* Some upgrades name the unit only partially, e.g. "Scout" for the Scout Cavalry */
+	let find_base_unit = |name: &str| {
+		let shrunk = util::shrink(name).to_lowercase();
+		
+		find_unit(name).or_else(|| shrunk_names.iter().position(|unit_name| unit_name.starts_with(&shrunk)))
+	};
+	
+	techs.iter()
+		.enumerate()
+		.filter(|&(_, tech)| !tech.for_what.is_empty())
+		.filter_map(|(i, tech)| {
+			let base_name = tech.for_what.split(',').next().unwrap_or("");
+			
+			match (find_base_unit(base_name), find_unit(&tech.name)) {
+				(Some(from), Some(to)) if from != to => Some(Upgrade { from: from, to: to, tech: i }),
+				_ => None
+			}
+		})
+		.collect()
+}
+
+/*
+ * Looks up an alias (ignoring case, spaces and dashes) and returns the actual name.
+ * Returns the name unchanged if it's not an alias.
+ */
+fn resolve_alias<'a>(aliases: &'a HashMap<String, String>, name: &'a str) -> &'a str {
+	aliases.iter()
+		.filter(|(alias, _)| util::shrink(alias).eq_ignore_ascii_case(&util::shrink(name)))
+		.map(|(_, actual)| actual.as_str())
+		.next()
+		.unwrap_or(name)
+}
+
+/*
+ * Ranks (name, candidate) pairs by how well the name matches, best match first.
+ * Candidates that don't match at all are dropped. The order of equally good candidates is kept.
+ */
+fn rank_by_name<'a, T, I>(candidates: I, name: &str) -> Vec<(usize, &'a str, T)> where I: Iterator<Item = (&'a str, T)> {
+	let mut ranked = candidates
+		.filter_map(|(candidate_name, candidate)| util::match_score(name, candidate_name).map(|score| (score, candidate_name, candidate)))
+		.collect::<Vec<_>>();
+	
+	ranked.sort_by_key(|(score, _, _)| *score);
+	
+	ranked
+}
+
+/*
+ * Picks the best matching candidate.
+ * Returns None if nothing matches or if differently named candidates match equally well.
+ */
+fn best_match<'a, T, I>(candidates: I, name: &str) -> Option<T> where I: Iterator<Item = (&'a str, T)> {
+	let mut ranked = rank_by_name(candidates, name).into_iter();
+	let (score, best_name, best) = ranked.next()?;
+	let ambiguous = ranked
+		.take_while(|(other_score, _, _)| *other_score == score)
+		.any(|(_, other_name, _)| !other_name.eq_ignore_ascii_case(best_name));
+	
+	if ambiguous {
+		None
+	} else {
+		Some(best)
+	}
+}
+
+/*
+ * Lists up to three distinct names similar to the given name, best match first.
+ */
+fn suggestions<'a, I>(names: I, name: &str) -> Vec<&'a str> where I: Iterator<Item = &'a str> {
+	let mut suggestions: Vec<&str> = Vec::new();
+	
+	for (_, candidate_name, _) in rank_by_name(names.map(|n| (n, ())), name) {
+		if suggestions.len() < 3 && !suggestions.iter().any(|s| s.eq_ignore_ascii_case(candidate_name)) {
+			suggestions.push(candidate_name);
+		}
+	}
+	
+	suggestions
+}
+
+/*
+ * Collects the civ specific notes for a civ from a civ bonus map.
+ * Keys like "Goths and allies" or "Aztec with goths ally" are matched by the civ name they start with.
+ * Keys like "Celtic allies" or "with Turkish ally" are matched by the civ adjective.
+ */
+fn civ_notes<S>(notes: &Option<Map<String, Value>>, civ: S) -> Vec<String> where S: AsRef<str> {
+	civ_note_entries(notes, civ)
+		.into_iter()
+		.map(|(key, note)| format!("{}: {}", key, note))
+		.collect()
+}
+
+/*
+ * Collects the keys and notes for a civ from a civ bonus map, matched like in civ_notes.
+ */
+fn civ_note_entries<S>(notes: &Option<Map<String, Value>>, civ: S) -> Vec<(&str, &str)> where S: AsRef<str> {
+	let civ = util::shrink(civ.as_ref());
+	
+	notes.iter()
+		.flat_map(|notes| notes.iter())
+		.filter(|(key, _)| key_civ(key).map(|key_civ| util::shrink(key_civ).eq_ignore_ascii_case(&civ)).unwrap_or(false))
+		.filter_map(|(key, note)| note.as_str().map(|note| (key.as_str(), note)))
+		.collect()
+}
+
+/*
+ * Gets the civ a civ bonus key starts with, skipping a leading "with" and mapping adjectives to the civ name.
+ */
+fn key_civ(key: &str) -> Option<&str> {
+	let mut words = key.split_whitespace();
+	let mut first = words.next()?;
+	
+	if first.eq_ignore_ascii_case("with") {
+		first = words.next()?;
+	}
+	
+	let civ = CIV_ADJECTIVES.iter()
+		.filter(|(adjective, _)| adjective.eq_ignore_ascii_case(first))
+		.map(|(_, civ)| *civ)
+		.next();
+	
+	Some(civ.unwrap_or(first))
+}
+
+/*
+ * Collects the notes which apply to the civ itself, the civ's own key ("Chinese") ranked before team bonus keys ("Britons and allies").
+ * Keys that only apply with a certain ally, like "Chinese with Vietnamese ally", are left out.
+ */
+fn own_civ_note_entries<S>(notes: &Option<Map<String, Value>>, civ: S) -> Vec<(&str, &str)> where S: AsRef<str> {
+	let mut entries = civ_note_entries(notes, civ)
+		.into_iter()
+		.filter(|(key, _)| !is_ally_conditional(key))
+		.collect::<Vec<_>>();
+	
+	entries.sort_by_key(|(key, _)| key.split_whitespace().count() > 1);
+	
+	entries
+}
+
+/*
+ * Whether a civ bonus key like "Chinese with Vietnamese ally" only applies with a certain ally.
+ */
+fn is_ally_conditional(key: &str) -> bool {
+	let words = key.split_whitespace().map(str::to_lowercase).collect::<Vec<_>>();
+	
+	words.iter().any(|word| word == "with") && words.last().map_or(false, |word| word == "ally" || word == "allies")
+}
+
+/*
+ * Parses a civ specific tech cost like "Free", "Cost *0.5 = 70G" or "90F in Feudal, 80F in Imperial Age".
+ * Returns None if the note is not about the cost.
+ */
+fn parse_civ_cost(note: &str, cost: &Cost) -> Option<Cost> {
+	let note = note.trim().to_lowercase();
+	
+	if note.starts_with("free") || note.starts_with("cost free") {
+		Some(Cost::default())
+	} else if note.starts_with("cost *") {
+		let factor: f32 = note["cost *".len()..].split_whitespace().next()?.parse().ok()?;
+		
+		Some(cost.discounted(((1.0 - factor) * 100.0).round().max(0.0) as u32))
+	} else if note.starts_with("cost ") || note.starts_with(|c: char| c.is_ascii_digit()) {
+		/* The rest of the note is an explanation like "(no gold cost)", not a qualifier of the cost */
+		Cost::parse(note.to_uppercase().rsplit(',').next()?).map(|cost| Cost { note: None, ..cost })
+	} else {
+		None
+	}
+}
+
+/*
+ * Parses a civ specific research time like "BT 0:33.3 - Barracks work 20% faster" or "Time required 0:43.5 in Castle, 0:41.7 in Imperial Age".
+ * Returns None if the note is not about the research time.
+ */
+fn parse_civ_time(note: &str) -> Option<Duration> {
+	let is_build_time = note.split_whitespace().any(|word| word.eq_ignore_ascii_case("bt"));
+	
+	if !is_build_time && !note.to_lowercase().contains("time required") {
+		return None;
+	}
+	
+	note.split_whitespace()
+		.filter_map(|word| parse_time(word.trim_matches(|c: char| c == ',' || c == '(' || c == ')')))
+		.last()
+}
+
+/*
+ * Parses the stat changes of a tech effect like "+1 AT +1 range +1 line of sight" or "Infantry movement speed * 1.1".
+ * Bonus damage ("+4 attack against spearman line") and stats units don't have (search radius, building armor) are skipped.
+ */
+fn parse_modifiers(effect: &str) -> Vec<Modifier> {
+	let words = effect.split_whitespace()
+		.map(|word| word.trim_matches(|c: char| c == ',' || c == '.' || c == '(' || c == ')').to_lowercase())
+		.collect::<Vec<_>>();
+	let word = |i: usize| words.get(i).map(|word| word.as_str()).unwrap_or("");
+	let mut modifiers = Vec::new();
+	
+	for i in 0..words.len() {
+		if word(i) == "*" {
+			let factor = if let Ok(factor) = word(i + 1).parse::<f32>() {
+				factor
+			} else {
+				continue;
+			};
+			let stat = if i > 0 { word(i - 1) } else { "" };
+			
+			match stat {
+				"hp" => modifiers.push(Modifier::HitPointFactor(factor)),
+				"speed" => modifiers.push(Modifier::MovementSpeedFactor(factor)),
+				_ => ()
+			}
+		} else if word(i).starts_with('+') {
+			let mut amounts = word(i).split('/').map(|amount| amount.trim_left_matches('+').parse::<i32>());
+			let first = if let Some(Ok(first)) = amounts.next() {
+				first
+			} else {
+				continue;
+			};
+			let second = amounts.next().and_then(Result::ok);
+			let (stat, next) = match word(i + 1) {
+				"pierce" if word(i + 2) == "attack" || word(i + 2) == "damage" => (word(i + 2), word(i + 3)),
+				stat => (stat, word(i + 2))
+			};
+			
+			if next == "against" || next == "vs" {
+				continue;
+			}
+			
+			match (stat, second) {
+				("attack", None) | ("at", None) | ("damage", None) | ("pierce", None) => modifiers.push(Modifier::Attack(first)),
+				("armor", Some(second)) => modifiers.push(Modifier::Armor(first, second)),
+				("range", None) => modifiers.push(Modifier::Range(first)),
+				("line", None) if next == "of" => modifiers.push(Modifier::LineOfSight(first)),
+				("hp", None) | ("hit", None) => modifiers.push(Modifier::HitPoints(first)),
+				_ => ()
+			}
+		}
+	}
+	
+	modifiers
+}
+
+/*
+ * Removes everything in parentheses.
+ */
+fn strip_parentheses(s: &str) -> String {
+	let mut text = String::new();
+	let mut depth = 0;
+	
+	for c in s.chars() {
+		match c {
+			'(' => depth += 1,
+			')' => depth -= 1,
+			_ if depth == 0 => text.push(c),
+			_ => ()
+		}
+	}
+	
+	text
+}
+
+/*
+ * Normalizes the name of a unit or class for comparing tech targets.
+ * Removes notes in parentheses like "(Heavy)", ignores case and makes every word singular.
+ */
+fn target_words(s: &str) -> String {
+	strip_parentheses(s).to_lowercase()
+		.split_whitespace()
+		.map(|word| word.trim_right_matches('s'))
+		.collect::<Vec<_>>()
+		.join(" ")
+}
+
+/*
+ * Parses the first word of a string as a number, e.g. "0.8 (+0.05/unit)".
+ */
+fn leading_number(s: &str) -> Option<f32> {
+	s.split_whitespace().next()?.parse().ok()
+}
+
+/*
+ * Parses an armor string like "0 / 1" into (melee armor, pierce armor).
+ * Anything after the pierce armor is ignored, e.g. "1 / 150 (2 / 8 when packed)".
+ */
+fn parse_armor(s: &str) -> Option<(i32, i32)> {
+	let mut parts = s.splitn(2, '/');
+	let melee = parts.next()?.trim().parse().ok()?;
+	let pierce = parts.next()?.split_whitespace().next()?.parse().ok()?;
+	
+	Some((melee, pierce))
+}
+
+/*
+ * Parses a time string like "0:21" or "0:21.7" (minutes:seconds).
+ */
+fn parse_time(s: &str) -> Option<Duration> {
+	let mut parts = s.trim().splitn(2, ':');
+	let minutes: u64 = parts.next()?.parse().ok()?;
+	let seconds: f64 = parts.next()?.parse().ok()?;
+	
+	if seconds < 0.0 {
+		return None;
+	}
+	
+	let millis = minutes * 60_000 + (seconds * 1000.0).round() as u64;
+	
+	Some(Duration::from_millis(millis))
+}
+
+/*
+ * Parses a range string like "4", "1 - 7" (minimum - maximum) or "-" (melee).
+ * Returns Some(None) for "-" and the maximum range otherwise.
+ */
+fn parse_range(s: &str) -> Option<Option<f32>> {
+	let s = s.trim();
+	
+	if s == "-" {
+		Some(None)
+	} else {
+		s.split('-').last()?.trim().parse().ok().map(Some)
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	
+	fn game_data() -> GameData {
+		GameData::new(
+			include_str!("../res/data/buildings.json"),
+			include_str!("../res/data/civs.json"),
+			include_str!("../res/data/techs.json"),
+			include_str!("../res/data/units.json"),
+			include_str!("../res/data/aliases.json")
+		).unwrap()
+	}
+	
+	/*
+	 * Fully upgraded (attack, armor, range, line of sight) of a unit without a civ.
+	 */
+	fn upgraded(data: &GameData, name: &str) -> (Option<i32>, Option<(i32, i32)>, Option<f32>, Option<i32>) {
+		let stats = data.upgraded_stats(data.unit_by_name(name).unwrap(), None, None);
+		
+		(stats.attack, stats.armor, stats.range, stats.line_of_sight)
+	}
+	
+	fn cost(food: u32, wood: u32, gold: u32, stone: u32) -> Cost {
+		Cost {
+			food: food,
+			wood: wood,
+			gold: gold,
+			stone: stone,
+			note: None
+		}
+	}
+	
+	#[test]
+	fn bundled_data_is_valid() {
+		assert_eq!(game_data().validate(), Vec::<String>::new());
+	}
+	
+	#[test]
+	fn upgraded_archers() {
+		let data = game_data();
+		
+		assert_eq!(upgraded(&data, "Arbalest"), (Some(10), Some((3, 4)), Some(8.0), Some(10)));
+		assert_eq!(upgraded(&data, "Paladin"), (Some(18), Some((5, 7)), None, Some(5)));
+	}
+	
+	#[test]
+	fn upgraded_gunpowder_units() {
+		let data = game_data();
+		
+		assert_eq!(upgraded(&data, "Hand Cannoneer"), (Some(17), Some((4, 4)), Some(7.0), Some(9)));
+		assert_eq!(upgraded(&data, "Elite Janissary"), (Some(22), Some((5, 4)), Some(8.0), Some(10)));
+		assert_eq!(upgraded(&data, "Elite Conquistador"), (Some(18), Some((5, 6)), Some(6.0), Some(8)));
+	}
+	
+	#[test]
+	fn upgraded_cavalry_archers() {
+		let data = game_data();
+		
+		assert_eq!(upgraded(&data, "Heavy Cavalry Archer").1, Some((5, 6)));
+		
+		/* Only one armor line, even though the Arambai is also cavalry */
+		let arambai = data.unit_upgrades(data.unit_by_name("Arambai").unwrap(), None)
+			.into_iter()
+			.map(|tech| tech.name.as_str())
+			.collect::<Vec<_>>();
+		
+		assert!(arambai.contains(&"Ring Archer Armor"));
+		assert!(!arambai.contains(&"Plate Barding Armor"));
+	}
+	
+	#[test]
+	fn gunpowder_unit_upgrades() {
+		let data = game_data();
+		let techs = data.unit_upgrades(data.unit_by_name("Elite Janissary").unwrap(), None);
+		let names = techs.iter().map(|tech| tech.name.as_str()).collect::<Vec<_>>();
+		let total = techs.iter().fold(Cost::default(), |total, tech| total + tech.cost.clone());
+		
+		assert_eq!(names, vec!["Elite Janissary", "Padded Archer Armor", "Leather Archer Armor", "Ring Archer Armor"]);
+		assert_eq!(total, cost(1350, 0, 1150, 0));
+	}
+	
+	#[test]
+	fn cost_parse() {
+		assert_eq!(Cost::parse("100F 40G"), Some(cost(100, 0, 40, 0)));
+		assert_eq!(Cost::parse("175W"), Some(cost(0, 175, 0, 0)));
+		assert_eq!(Cost::parse("Free"), Some(Cost::default()));
+		assert_eq!(Cost::parse("-"), None);
+	}
+	
+	#[test]
+	fn cost_parse_note() {
+		assert_eq!(Cost::parse("200G/enemy villager").and_then(|cost| cost.note), Some(String::from("/enemy villager")));
+		assert_eq!(Cost::parse("400G each time").and_then(|cost| cost.note), Some(String::from("each time")));
+		assert_eq!(Cost::parse("Free to research").and_then(|cost| cost.note), Some(String::from("to research")));
+	}
+	
+	#[test]
+	fn cost_discounted() {
+		assert_eq!(cost(100, 0, 40, 0).discounted(25), cost(75, 0, 30, 0));
+		assert_eq!(cost(0, 0, 475, 0).discounted(50), cost(0, 0, 238, 0));
+		assert_eq!(cost(0, 0, 65, 0).discounted(10), cost(0, 0, 59, 0));
+		assert_eq!(cost(100, 0, 0, 0).discounted(150), Cost::default());
+	}
+	
+	#[test]
+	fn cost_display() {
+		assert_eq!(cost(100, 0, 40, 0).to_string(), "100F 40G");
+		assert_eq!(Cost::default().to_string(), "nothing");
+		assert_eq!(Cost::parse("200G/enemy villager").unwrap().to_string(), "200G/enemy villager");
+		assert_eq!(Cost::parse("400G each time").unwrap().to_string(), "400G each time");
+		assert_eq!(Cost::parse("Free to research").unwrap().to_string(), "nothing to research");
+	}
+	
+	#[test]
+	fn civ_cost_free() {
+		assert_eq!(parse_civ_cost("Free", &cost(150, 0, 0, 0)), Some(Cost::default()));
+		assert_eq!(parse_civ_cost("Cost free", &cost(150, 0, 0, 0)), Some(Cost::default()));
+		assert_eq!(parse_civ_cost("Free, but mill is required", &cost(75, 0, 0, 0)), Some(Cost::default()));
+	}
+	
+	#[test]
+	fn civ_cost_factor() {
+		assert_eq!(parse_civ_cost("Cost *0.5 = 70G", &cost(0, 0, 140, 0)), Some(cost(0, 0, 70, 0)));
+	}
+	
+	#[test]
+	fn civ_cost_amounts() {
+		assert_eq!(parse_civ_cost("Cost 100F (no gold cost)", &cost(100, 0, 50, 0)), Some(cost(100, 0, 0, 0)));
+		assert_eq!(parse_civ_cost("Cost 200F 250W (50% bonus)", &cost(400, 500, 0, 0)), Some(cost(200, 250, 0, 0)));
+		assert_eq!(parse_civ_cost("90F 36G in Feudal, 85F 34G in Castle, 80F 32G in Imperial Age", &cost(100, 0, 40, 0)), Some(cost(80, 0, 32, 0)));
+	}
+	
+	#[test]
+	fn civ_cost_other_notes() {
+		assert_eq!(parse_civ_cost("gives monks +5 HP", &cost(0, 0, 300, 0)), None);
+		assert_eq!(parse_civ_cost("Receive 100F 100G when finished", &cost(0, 0, 0, 0)), None);
+		assert_eq!(parse_civ_cost("BT 0:33.3 - Barracks work 20% faster", &cost(175, 0, 0, 0)), None);
+		assert_eq!(parse_civ_cost("0:27.8 BT (80% faster)", &cost(175, 0, 0, 0)), None);
+	}
+	
+	#[test]
+	fn civ_time() {
+		assert_eq!(parse_civ_time("BT 0:33.3 - Barracks work 20% faster"), Some(Duration::from_millis(33_300)));
+		assert_eq!(parse_civ_time("0:27.8 BT (80% faster)"), Some(Duration::from_millis(27_800)));
+		assert_eq!(parse_civ_time("Time required 0:43.5 in Castle, 0:41.7 in Imperial Age"), Some(Duration::from_millis(41_700)));
+		assert_eq!(parse_civ_time("Time required 2:25.5 (TC work rate bonus 10% in Feudal)"), Some(Duration::from_millis(145_500)));
+	}
+	
+	#[test]
+	fn civ_time_other_notes() {
+		assert_eq!(parse_civ_time("Free"), None);
+		assert_eq!(parse_civ_time("Cost 100F (no gold cost)"), None);
+		assert_eq!(parse_civ_time("Pays off the debt 0:30 later"), None);
+	}
+	
+	#[test]
+	fn civ_bonus_keys() {
+		assert_eq!(key_civ("Goths and allies"), Some("Goths"));
+		assert_eq!(key_civ("Celtic allies"), Some("Celts"));
+		assert_eq!(key_civ("with Turkish ally"), Some("Turks"));
+	}
+	
+	#[test]
+	fn ally_conditional_keys() {
+		assert!(is_ally_conditional("Chinese with Vietnamese ally"));
+		assert!(is_ally_conditional("Britons and allies with Berber ally"));
+		assert!(!is_ally_conditional("Britons and allies"));
+		assert!(!is_ally_conditional("Huns ally"));
+	}
 }
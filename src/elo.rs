@@ -1,19 +1,43 @@
-use std::sync::RwLock;
-
 use rocket::State;
 
 use util::{
 	self,
-	NightbotHeaderFields
+	NightbotHeaderFields,
+	OutputFormat,
+	Output
+};
+use voobly::{
+	VooblyApi,
+	VooblyError
 };
-use voobly::VooblyApi;
+use config::ChannelConfig;
+
+/*
+ * Default answer templates. Channels can override them with the keys "elo" and "elo_unrated".
+ */
+const ELO_TEMPLATE: &'static str = "{name} is rated {elo} in {ladder}.";
+const ELO_UNRATED_TEMPLATE: &'static str = "{name} is not rated in {ladder}.";
 
 /*
  * Possible query parameters passed to the elo resource.
  */
 #[derive(FromForm)]
 pub struct VooblyLadderInfo {
-	ladder: Option<String>
+	ladder: Option<String>,
+	/* Read by the OutputFormat guard */
+	#[allow(dead_code)]
+	format: Option<String>
+}
+
+/*
+ * JSON payload of the elo resource.
+ */
+#[derive(Serialize)]
+struct EloPayload {
+	name: String,
+	ladder: String,
+	elo: Option<String>,
+	name_guessed: bool
 }
 
 /*
@@ -32,59 +56,75 @@ fn parse_ladder(ladder: &VooblyLadderInfo) -> (String, String) {
 
 /*
  * Fetches the elo of a player.
- * If the player doesn't exist, None is returned.
- * If the player is not rated, Some(None, ..., ..., ...) is returned.
+ * If the player doesn't exist or Voobly fails, the error is returned.
+ * If the player is not rated, Ok(None, ..., ..., ...) is returned.
  * Voobly has a small tolerance for misspelled names. If the name didn't exist and Voobly guessed it, name_guessed is true.
  */
-fn fetch_elo<S>(api: &mut VooblyApi, passed_name: S, ladder: VooblyLadderInfo) -> Option<(Option<String>, String, bool, String)> where S: AsRef<str> {
+fn fetch_elo<S>(api: &VooblyApi, passed_name: S, ladder: VooblyLadderInfo) -> Result<(Option<String>, String, bool, String), VooblyError> where S: AsRef<str> {
 	let passed_name = passed_name.as_ref();
 	let (ladder, ladder_canonical) = parse_ladder(&ladder);
 	let (id, name) = api.user_info(passed_name)?;
 	let name_guessed = !name.eq_ignore_ascii_case(passed_name);
-	let elo = api.elo(id, ladder);
+	let elo = api.elo(id, ladder)?;
 	
-	Some((elo, name, name_guessed, ladder_canonical))
+	Ok((elo, name, name_guessed, ladder_canonical))
 }
 
 /*
  * Request handler for the elo resource.
  * Constructs a response based on the result of the request to the Voobly API.
- * "api_lock" is the Voobly API struct kept persistent between requests by Rocket.
+ * "api" is the Voobly API struct kept persistent between requests by Rocket.
+ * "config" is the channel config kept persistent between requests by Rocket.
  * Calls the same resource but with default query parameters "ladder=rm1v1".
+ * "format" is the requested output format.
  * Only accepts the request if the Nightbot headers are present.
  */
 #[get("/elo/<voobly_user>")]
-pub fn elo(api_lock: State<RwLock<VooblyApi>>, voobly_user: String, nightbot_headers: NightbotHeaderFields) -> String {
-	let ladder = VooblyLadderInfo { ladder: Some(String::from("rm1v1")) };
+pub fn elo(api: State<VooblyApi>, config: State<ChannelConfig>, voobly_user: String, format: OutputFormat, nightbot_headers: NightbotHeaderFields) -> Output {
+	let ladder = VooblyLadderInfo { ladder: Some(String::from("rm1v1")), format: None };
 	
-	elo_with_ladder(api_lock, voobly_user, ladder, nightbot_headers)
+	elo_with_ladder(api, config, voobly_user, ladder, format, nightbot_headers)
 }
 
 /*
  * Request handler for the elo resource.
  * Constructs a response based on the result of the request to the Voobly API.
- * "api_lock" is the Voobly API struct kept persistent between requests by Rocket.
- * "ladder" are the query parameters (ladder). They might be None or empty.
+ * "api" is the Voobly API struct kept persistent between requests by Rocket.
+ * "config" is the channel config kept persistent between requests by Rocket.
+ * "ladder" are the query parameters (ladder, format). They might be None or empty.
+ * "format" is the requested output format.
  * Only accepts the request if the Nightbot headers are present.
  */
 #[get("/elo/<voobly_user>?<ladder>")]
-pub fn elo_with_ladder(api_lock: State<RwLock<VooblyApi>>, voobly_user: String, ladder: VooblyLadderInfo, nightbot_headers: NightbotHeaderFields) -> String {
-	let mut api = api_lock.write().unwrap();
-	let elo_info = if let Some((elo, name, name_guessed, ladder_canonical)) = fetch_elo(&mut api, &voobly_user, ladder) {
-		let correction = if name_guessed {
-			format!("Did you mean {}? ", name)
-		} else {
-			String::new()
-		};
-		
-		if let Some(elo) = elo {
-			format!("{}{} is rated {} in {}.", correction, name, elo, ladder_canonical)
-		} else {
-			format!("{}{} is not rated in {}.", correction, name, ladder_canonical)
+pub fn elo_with_ladder(api: State<VooblyApi>, config: State<ChannelConfig>, voobly_user: String, ladder: VooblyLadderInfo, format: OutputFormat, nightbot_headers: NightbotHeaderFields) -> Output {
+	let templates = config.templates(&nightbot_headers);
+	let elo_info = match fetch_elo(&api, &voobly_user, ladder) {
+		Ok((elo, name, name_guessed, ladder_canonical)) => {
+			let correction = if name_guessed {
+				format!("Did you mean {}? ", name)
+			} else {
+				String::new()
+			};
+			let answer = if let Some(ref elo) = elo {
+				templates.render("elo", ELO_TEMPLATE, &[("name", name.clone()), ("elo", elo.clone()), ("ladder", ladder_canonical.clone())])
+			} else {
+				templates.render("elo_unrated", ELO_UNRATED_TEMPLATE, &[("name", name.clone()), ("ladder", ladder_canonical.clone())])
+			};
+			let payload = EloPayload {
+				name: name,
+				ladder: ladder_canonical,
+				elo: elo,
+				name_guessed: name_guessed
+			};
+			
+			Ok((format!("{}{}", correction, answer), payload))
+		},
+		Err(error) => {
+			error.log();
+			
+			Err(String::from(error.answer()))
 		}
-	} else {
-		String::from("That user doesn't exist.")
 	};
 	
-	util::create_response(elo_info, &nightbot_headers)
+	util::create_output(elo_info, format, &nightbot_headers)
 }
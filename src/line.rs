@@ -0,0 +1,126 @@
+use std::sync::RwLock;
+use std::time::Duration;
+
+use rocket::State;
+
+use util::{
+	self,
+	NightbotHeaderFields
+};
+use data::{
+	GameData,
+	GameVersion,
+	Cost
+};
+use config::ChannelConfig;
+
+/*
+ * Possible query parameters passed to the line resource.
+ */
+#[derive(FromForm)]
+pub struct LineInfo {
+	civ: Option<String>,
+	version: Option<String>
+}
+
+/*
+ * Lists the units of the line the specified unit belongs to.
+ * Every upgrade shows the cost and research time of all upgrades up to it.
+ * If a civ is given, the upgrades the civ cannot research are listed.
+ */
+fn fetch_unit_line<S>(data: &GameData, name: S, civ: Option<&String>, version: Option<GameVersion>) -> String where S: AsRef<str> {
+	let unit = if let Some(unit) = data.unit_by_name(&name) {
+		unit
+	} else {
+		return util::with_suggestions("That unit does not exist.", &data.unit_suggestions(&name));
+	};
+	let civ = if let Some(civ) = civ {
+		if let Some(civ) = data.civ_by_name(civ) {
+			Some(civ)
+		} else {
+			return util::with_suggestions("That civ does not exist.", &data.civ_suggestions(civ));
+		}
+	} else {
+		None
+	};
+	let correction = data.correction(name.as_ref(), &unit.name);
+	let not_in_version = util::not_in_version(&unit.name, unit.version(), version)
+		.or_else(|| civ.and_then(|civ| util::not_in_version(&civ.name, civ.version(), version)));
+	
+	if let Some(not_in_version) = not_in_version {
+		return format!("{}{}", correction, not_in_version);
+	}
+	
+	let line = data.unit_line(unit)
+		.into_iter()
+		.take_while(|step| version.map_or(true, |version| version.includes(step.unit.version())))
+		.collect::<Vec<_>>();
+	
+	if line.len() < 2 {
+		return format!("{}{} has no upgrades.", correction, unit.name);
+	}
+	
+	let mut cost = Cost::default();
+	let mut time = Duration::from_secs(0);
+	let mut steps = vec![line[0].unit.name.clone()];
+	
+	for tech in line.iter().filter_map(|step| step.tech) {
+		cost = cost + tech.cost.clone();
+		time += tech.research_time().unwrap_or_else(|| Duration::from_secs(0));
+		steps.push(format!("{} ({}, {})", tech.name, cost, util::format_duration(time)));
+	}
+	
+	let line_info = format!("{}{} line: {}.", correction, line[0].unit.name, steps.join(" > "));
+	
+	if let Some(civ) = civ {
+		/* Later upgrades require the earlier ones, so everything after the first missing upgrade is missing as well */
+		let missing = line.iter()
+			.skip_while(|step| match step.tech.map_or_else(|| step.unit.available_to(&civ.name), |tech| tech.available_to(&civ.name)) {
+				Some((false, _)) => false,
+				_ => true
+			})
+			.map(|step| step.unit.name.as_str())
+			.collect::<Vec<_>>();
+		
+		if missing.is_empty() {
+			format!("{} {} get the full line.", line_info, civ.name)
+		} else {
+			format!("{} {} miss {}.", line_info, civ.name, missing.join(", "))
+		}
+	} else {
+		line_info
+	}
+}
+
+/*
+ * Request handler for the line resource.
+ * Lists the unit line of the specified unit with the cumulative upgrade cost.
+ * "data_lock" is the GameData struct kept persistent between requests by Rocket.
+ * "config" is the channel config kept persistent between requests by Rocket.
+ * Only accepts the request if the Nightbot headers are present.
+ */
+#[get("/line/<name>")]
+pub fn line(data_lock: State<RwLock<GameData>>, config: State<ChannelConfig>, name: String, nightbot_headers: NightbotHeaderFields) -> String {
+	let line_info = LineInfo { civ: None, version: None };
+	
+	line_with_info(data_lock, config, name, line_info, nightbot_headers)
+}
+
+/*
+ * Request handler for the line resource.
+ * Lists the unit line of the specified unit with the cumulative upgrade cost.
+ * "data_lock" is the GameData struct kept persistent between requests by Rocket.
+ * "config" is the channel config kept persistent between requests by Rocket.
+ * "line_info" are the query parameters (civ, version). They might be None.
+ * Only accepts the request if the Nightbot headers are present.
+ */
+#[get("/line/<name>?<line_info>")]
+pub fn line_with_info(data_lock: State<RwLock<GameData>>, config: State<ChannelConfig>, name: String, line_info: LineInfo, nightbot_headers: NightbotHeaderFields) -> String {
+	let data = data_lock.read().unwrap();
+	let line_info = match config.game_version(&nightbot_headers, line_info.version.as_ref()) {
+		Ok(version) => fetch_unit_line(&data, &name, line_info.civ.as_ref(), version),
+		Err(_) => String::from("That game version does not exist.")
+	};
+	
+	util::create_response(line_info, &nightbot_headers)
+}
@@ -23,17 +23,61 @@ mod unit;
 mod available;
 mod building;
 mod score;
+mod civ;
+mod vs;
+mod techtree;
+mod compare;
+mod line;
+mod upgradecost;
+mod age;
+mod admin;
+mod config;
+mod template;
+mod coalesce;
+mod cache;
 
 use std::env;
 use std::sync::RwLock;
 
 use voobly::VooblyApi;
 use data::GameData;
+use config::ChannelConfig;
 
 const BUILDING_DATA: &'static str = include_str!("../res/data/buildings.json");
 const CIV_DATA: &'static str = include_str!("../res/data/civs.json");
 const TECH_DATA: &'static str = include_str!("../res/data/techs.json");
 const UNIT_DATA: &'static str = include_str!("../res/data/units.json");
+const ALIAS_DATA: &'static str = include_str!("../res/data/aliases.json");
+
+/*
+ * Loads the game data from the directory in the environment variable.
+ * Falls back to the game data embedded into the binary if the variable is not set or the data is broken.
+ */
+fn load_game_data() -> GameData {
+	if let Ok(dir) = env::var(admin::DATA_DIR_VAR) {
+		match GameData::load(&dir) {
+			Ok(data) => return data,
+			Err(e) => eprintln!("Could not load game data from {}: {}", dir, e)
+		}
+	}
+	
+	GameData::new(BUILDING_DATA, CIV_DATA, TECH_DATA, UNIT_DATA, ALIAS_DATA).expect("Embedded game data")
+}
+
+/*
+ * Loads the channel config from the file in the environment variable.
+ * Falls back to an empty config if the variable is not set or the file is broken.
+ */
+fn load_channel_config() -> ChannelConfig {
+	if let Ok(path) = env::var(config::CONFIG_FILE_VAR) {
+		match ChannelConfig::load(&path) {
+			Ok(config) => return config,
+			Err(e) => eprintln!("Could not load channel config from {}: {}", path, e)
+		}
+	}
+	
+	Default::default()
+}
 
 /*
  * Loads the Voobly API key from the environment variable, creates a Voobly API struct and launches Rocket.
@@ -54,13 +98,14 @@ fn main() {
 		Default::default()
 	};
 	let api = VooblyApi::new(api_key, user, pass);
-	let api = RwLock::new(api);
-	let data = GameData::new(BUILDING_DATA, CIV_DATA, TECH_DATA, UNIT_DATA);
+	let data = load_game_data();
 	let data = RwLock::new(data);
+	let config = load_channel_config();
 	
 	rocket::ignite()
 		.manage(api)
 		.manage(data)
-		.mount("/", routes![elo::elo, elo::elo_with_ladder, tech::tech, unit::unit, available::available, building::building, score::score])
+		.manage(config)
+		.mount("/", routes![elo::elo, elo::elo_with_ladder, tech::tech, tech::tech_with_info, unit::unit, unit::unit_with_info, available::available, available::available_with_info, available::who, building::building, building::building_with_info, score::score, score::score_with_info, civ::civ, civ::civ_bonus, civ::civ_bonus_with_info, civ::civ_team_bonus, civ::civ_unique_unit, civ::civ_unique_tech, vs::vs, techtree::techtree, techtree::techtree_with_info, compare::compare, compare::compare_with_info, line::line, line::line_with_info, upgradecost::upgrade_cost, upgradecost::upgrade_cost_with_info, age::age, age::age_with_info, admin::reload])
 		.launch();
 }
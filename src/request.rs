@@ -59,51 +59,52 @@ fn update_cookies(cookie_jar: &mut CookieJar, set_cookie_header: &SetCookieHeade
 	}
 }
 
+/*
+ * Reads the body of a response.
+ * Server errors are returned as error, since the body is just an error page then.
+ */
+fn read_response(response: &mut Response) -> Result<String, String> {
+	if response.status().is_server_error() {
+		return Err(format!("{} answered with {}", response.url(), response.status()));
+	}
+	
+	response.text().map_err(|e| e.to_string())
+}
+
 /*
  * Dead simple HTTP GET request.
+ * The error describes why the request failed.
  */
-pub fn get<U>(url: U) -> Option<String> where U: IntoUrl {
+pub fn get<U>(url: U) -> Result<String, String> where U: IntoUrl {
 	let client = create_client();
+	let mut response = client.get(url).send().map_err(|e| e.to_string())?;
 	
-	client.get(url)
-		.send()
-		.ok()
-		.as_mut()
-		.map(Response::text)
-		.and_then(Result::ok)
+	read_response(&mut response)
 }
 
-pub fn get_with_cookies<U>(url: U, cookie_jar: &mut CookieJar) -> Option<String> where U: IntoUrl {
+pub fn get_with_cookies<U>(url: U, cookie_jar: &mut CookieJar) -> Result<String, String> where U: IntoUrl {
 	let client = create_client();
 	let cookie_header = create_cookie_header(cookie_jar);
-	let response = client.get(url).header(cookie_header).send();
+	let mut response = client.get(url).header(cookie_header).send().map_err(|e| e.to_string())?;
 	
-	if let Ok(mut response) = response {
-		if let Some(set_cookie_header) = response.headers().get::<SetCookieHeader>() {
-			update_cookies(cookie_jar, set_cookie_header);
-		}
-		
-		response.text().ok()
-	} else {
-		None
+	if let Some(set_cookie_header) = response.headers().get::<SetCookieHeader>() {
+		update_cookies(cookie_jar, set_cookie_header);
 	}
+	
+	read_response(&mut response)
 }
 
-pub fn post_with_cookies<U, S>(url: U, cookie_jar: &mut CookieJar, form_data: Vec<(S, S)>) -> Option<String> where U: IntoUrl, S: AsRef<str> {
+pub fn post_with_cookies<U, S>(url: U, cookie_jar: &mut CookieJar, form_data: Vec<(S, S)>) -> Result<String, String> where U: IntoUrl, S: AsRef<str> {
 	let client = create_client();
 	let form_data = form_data.iter()
 		.map(|(k, v)| (k.as_ref(), v.as_ref()))
 		.collect::<HashMap<&str, &str>>();
 	let cookie_header = create_cookie_header(cookie_jar);
-	let response = client.post(url).form(&form_data).header(cookie_header).send();
+	let mut response = client.post(url).form(&form_data).header(cookie_header).send().map_err(|e| e.to_string())?;
 	
-	if let Ok(mut response) = response {
-		if let Some(set_cookie_header) = response.headers().get::<SetCookieHeader>() {
-			update_cookies(cookie_jar, set_cookie_header);
-		}
-		
-		response.text().ok()
-	} else {
-		None
+	if let Some(set_cookie_header) = response.headers().get::<SetCookieHeader>() {
+		update_cookies(cookie_jar, set_cookie_header);
 	}
+	
+	read_response(&mut response)
 }
@@ -1,5 +1,3 @@
-use std::sync::RwLock;
-
 use rocket::State;
 use time::{
 	self,
@@ -9,11 +7,22 @@ use time::{
 
 use util::{
 	self,
-	NightbotHeaderFields
+	NightbotHeaderFields,
+	OutputFormat,
+	Output
 };
 use voobly::VooblyApi;
+use config::ChannelConfig;
 
 const MATCH_TIME_DIFFERENCE_THRESHOLD_HOURS: i64 = 5;
+const MATCHES_PER_PAGE: usize = 10;
+/* Each page of the match history is a request to Voobly, so the number of games is limited */
+const MAX_GAMES: usize = 50;
+
+/*
+ * Default answer template. Channels can override it with the key "score".
+ */
+const SCORE_TEMPLATE: &'static str = "{note}{win_text}, {loss_text}";
 
 /*
  * Info for match data.
@@ -25,6 +34,27 @@ struct MatchData {
 	losers: Vec<(Option<String>, String)>
 }
 
+/*
+ * Possible query parameters passed to the score resource.
+ */
+#[derive(FromForm)]
+pub struct ScoreInfo {
+	games: Option<usize>,
+	/* Read by the OutputFormat guard */
+	#[allow(dead_code)]
+	format: Option<String>
+}
+
+/*
+ * JSON payload of the score resource.
+ */
+#[derive(Serialize)]
+struct ScorePayload {
+	name: String,
+	wins: u32,
+	losses: u32
+}
+
 /*
  * Extracts names (clan tag, name) from links in the voobly match table.
  */
@@ -148,50 +178,90 @@ fn parse_score(name: &str, matches: &Vec<MatchData>) -> (u32, u32) {
 
 /*
  * Resource to fetch the score for the last games played by someone.
- * "api_lock" is the Voobly API struct kept persistent between requests by Rocket.
+ * "api" is the Voobly API struct kept persistent between requests by Rocket.
+ * "config" is the channel config kept persistent between requests by Rocket.
+ * "format" is the requested output format.
  * Only accepts the request if the Nightbot headers are present.
  */
 #[get("/score/<voobly_user>")]
-pub fn score(api_lock: State<RwLock<VooblyApi>>, voobly_user: String, nightbot_headers: NightbotHeaderFields) -> String {
-	let mut api = api_lock.write().unwrap();
+pub fn score(api: State<VooblyApi>, config: State<ChannelConfig>, voobly_user: String, format: OutputFormat, nightbot_headers: NightbotHeaderFields) -> Output {
+	score_with_info(api, config, voobly_user, ScoreInfo { games: None, format: None }, format, nightbot_headers)
+}
+
+/*
+ * Resource to fetch the score for the last games played by someone.
+ * "api" is the Voobly API struct kept persistent between requests by Rocket.
+ * "config" is the channel config kept persistent between requests by Rocket.
+ * "score_info" are the query parameters (games, format). They might be None.
+ * "format" is the requested output format.
+ * Only accepts the request if the Nightbot headers are present.
+ */
+#[get("/score/<voobly_user>?<score_info>")]
+pub fn score_with_info(api: State<VooblyApi>, config: State<ChannelConfig>, voobly_user: String, score_info: ScoreInfo, format: OutputFormat, nightbot_headers: NightbotHeaderFields) -> Output {
+	let templates = config.templates(&nightbot_headers);
+	let games = score_info.games.unwrap_or(MATCHES_PER_PAGE).max(1).min(MAX_GAMES);
+	let pages = ((games + MATCHES_PER_PAGE - 1) / MATCHES_PER_PAGE) as u16;
 	let name_and_matches = api.user_info(voobly_user)
-		.and_then(|(id, name)| api.matches(id, 0).map(|matches| (name, matches)));
-	let response = if let Some((name, matches)) = name_and_matches {
-		let mut match_list = Vec::new();
-		
-		/* Extract raw match data from match table */
-		for match_row in matches.iter().skip(1) {
-			let mut entries = match_row.iter().skip(2);
+		.and_then(|(id, name)| api.match_history(id, pages).map(|matches| (name, matches)));
+	let response = match name_and_matches {
+		Ok((name, matches)) => {
+			let mut match_list = Vec::new();
 			
-			if let (Some(time), Some(winners), Some(losers)) = (entries.next(), entries.next(), entries.next()) {
-				if let Some(match_data) = parse_match_data(time, winners, losers) {
-					match_list.push(match_data);
+			/* Extract raw match data from match tables */
+			for match_row in matches.iter().flat_map(|match_data| match_data.iter().skip(1)) {
+				let mut entries = match_row.iter().skip(2);
+				
+				if let (Some(time), Some(winners), Some(losers)) = (entries.next(), entries.next(), entries.next()) {
+					if let Some(match_data) = parse_match_data(time, winners, losers) {
+						match_list.push(match_data);
+					}
 				}
 			}
+			
+			match_list.truncate(games);
+			
+			let (wins, losses) = parse_score(&name, &match_list);
+			
+			let win_answer = if wins == 1 {
+				format!("{} win", wins)
+			} else {
+				format!("{} wins", wins)
+			};
+			let loss_answer = if losses == 1 {
+				format!("{} loss", losses)
+			} else {
+				format!("{} losses", losses)
+			};
+			let note = if wins + losses == games as u32 && games == 1 {
+				String::from("Last game: ")
+			} else if wins + losses == games as u32 {
+				format!("Last {} games: ", games)
+			} else {
+				String::new()
+			};
+			
+			let answer = templates.render("score", SCORE_TEMPLATE, &[
+				("name", name.clone()),
+				("wins", wins.to_string()),
+				("losses", losses.to_string()),
+				("win_text", win_answer),
+				("loss_text", loss_answer),
+				("note", note)
+			]);
+			let payload = ScorePayload {
+				name: name,
+				wins: wins,
+				losses: losses
+			};
+			
+			Ok((answer, payload))
+		},
+		Err(error) => {
+			error.log();
+			
+			Err(String::from(error.answer()))
 		}
-		
-		let (wins, losses) = parse_score(&name, &match_list);
-		
-		let win_answer = if wins == 1 {
-			format!("{} win", wins)
-		} else {
-			format!("{} wins", wins)
-		};
-		let loss_answer = if losses == 1 {
-			format!("{} loss", losses)
-		} else {
-			format!("{} losses", losses)
-		};
-		let note = if wins + losses == 10 {
-			"Last 10 games: "
-		} else {
-			""
-		};
-		
-		format!("{}{}, {}", note, win_answer, loss_answer)
-	} else {
-		String::from("That user doesn't exist.")
 	};
 	
-	util::create_response(response, &nightbot_headers)
+	util::create_output(response, format, &nightbot_headers)
 }
@@ -4,47 +4,153 @@ use rocket::State;
 
 use util::{
 	self,
-	NightbotHeaderFields
+	NightbotHeaderFields,
+	OutputFormat,
+	Output
 };
-use data::GameData;
+use data::{
+	GameData,
+	GameVersion,
+	Tech
+};
+use config::ChannelConfig;
+use template::Templates;
+
+/*
+ * Default answer template. Channels can override it with the key "tech".
+ */
+const TECH_TEMPLATE: &'static str = "{name} ({type}{version}) costs {cost}, is available in {age}, takes {time} to research.{description}";
+
+/*
+ * Possible query parameters passed to the tech resource.
+ */
+#[derive(FromForm)]
+pub struct TechInfo {
+	civ: Option<String>,
+	version: Option<String>,
+	/* Read by the OutputFormat guard */
+	#[allow(dead_code)]
+	format: Option<String>
+}
+
+/*
+ * JSON payload of the tech resource.
+ */
+#[derive(Serialize)]
+struct TechPayload<'a> {
+	tech: &'a Tech,
+	civ: Option<&'a str>,
+	available: bool,
+	civ_notes: Vec<String>
+}
 
 /*
  * Fetches the data for the specified tech name.
+ * If a civ is given, the civ specific notes are appended.
+ * If a game version is given, only things that are part of it are considered.
+ * The chat text is rendered from the channel's templates.
+ * Returns the chat text and the JSON payload, or the answer if the tech could not be looked up.
  */
-fn fetch_tech_data<S>(data: &GameData, name: S) -> Option<String> where S: AsRef<str> {
-	let tech = data.tech_by_name(&name);
-	
-	tech.map(|tech| {
-		let description = if !tech.for_what.is_empty() {
-			if let Some(unit) = data.unit_by_name(&tech.for_what) {
-				format!(" Upgrades {}.", unit.name)
-			} else if let Some(building) = data.building_by_name(&tech.for_what) {
-				format!(" Upgrades {}.", building.name)
-			} else {
-				format!(" Effects: {}.", tech.for_what)
-			}
+fn fetch_tech_data<'a, S>(data: &'a GameData, name: S, civ: Option<&String>, version: Option<GameVersion>, templates: &Templates) -> Option<Result<(String, TechPayload<'a>), String>> where S: AsRef<str> {
+	let tech = data.tech_by_name(&name)?;
+	let civ = if let Some(civ) = civ {
+		if let Some(civ) = data.civ_by_name(civ) {
+			Some(civ)
+		} else {
+			return Some(Err(util::with_suggestions("That civ does not exist.", &data.civ_suggestions(civ))));
+		}
+	} else {
+		None
+	};
+	let correction = data.correction(name.as_ref(), &tech.name);
+	let not_in_version = util::not_in_version(&tech.name, tech.version(), version)
+		.or_else(|| civ.and_then(|civ| util::not_in_version(&civ.name, civ.version(), version)));
+	
+	if let Some(not_in_version) = not_in_version {
+		return Some(Err(format!("{}{}", correction, not_in_version)));
+	}
+	
+	let mut payload = TechPayload {
+		tech: tech,
+		civ: civ.map(|civ| civ.name.as_str()),
+		available: true,
+		civ_notes: Vec::new()
+	};
+	
+	if let Some(civ) = civ {
+		if let Some((false, civ_name)) = tech.available_to(&civ.name) {
+			payload.available = false;
+			
+			return Some(Ok((format!("{}{} cannot research {}.", correction, civ_name, tech.name), payload)));
+		}
+	}
+	
+	let description = if !tech.for_what.is_empty() {
+		if let Some(unit) = data.unit_by_name(&tech.for_what) {
+			format!(" Upgrades {}.", unit.name)
+		} else if let Some(building) = data.building_by_name(&tech.for_what) {
+			format!(" Upgrades {}.", building.name)
 		} else {
-			String::new()
-		};
-		
-		format!("{} ({}) costs {}, takes {} to research.{}", tech.name, tech.type_name, tech.cost, tech.time, description)
-	})
+			format!(" Effects: {}.", tech.for_what)
+		}
+	} else {
+		String::new()
+	};
+	let version_name = tech.version().map(|version| format!(", {}", version.name())).unwrap_or_default();
+	let tech_info = templates.render("tech", TECH_TEMPLATE, &[
+		("name", tech.name.clone()),
+		("type", tech.type_name.clone()),
+		("version", version_name),
+		("cost", tech.cost.to_string()),
+		("age", tech.age.to_string()),
+		("time", tech.time.clone()),
+		("description", description)
+	]);
+	let civ_notes = civ.map(|civ| tech.civ_notes(&civ.name)).unwrap_or_else(Vec::new);
+	let tech_info = if civ_notes.is_empty() {
+		format!("{}{}", correction, tech_info)
+	} else {
+		format!("{}{} {}.", correction, tech_info, civ_notes.join("; "))
+	};
+	
+	payload.civ_notes = civ_notes;
+	
+	Some(Ok((tech_info, payload)))
 }
 
 /*
  * Request handler for the tech resource.
  * Grabs the specified tech from the game data and processes it.
  * "data_lock" is the GameData struct kept persistent between requests by Rocket.
+ * "config" is the channel config kept persistent between requests by Rocket.
+ * "format" is the requested output format.
  * Only accepts the request if the Nightbot headers are present.
  */
 #[get("/tech/<name>")]
-pub fn tech(data_lock: State<RwLock<GameData>>, name: String, nightbot_headers: NightbotHeaderFields) -> String {
+pub fn tech(data_lock: State<RwLock<GameData>>, config: State<ChannelConfig>, name: String, format: OutputFormat, nightbot_headers: NightbotHeaderFields) -> Output {
+	let tech_info = TechInfo { civ: None, version: None, format: None };
+	
+	tech_with_info(data_lock, config, name, tech_info, format, nightbot_headers)
+}
+
+/*
+ * Request handler for the tech resource.
+ * Grabs the specified tech from the game data and processes it.
+ * "data_lock" is the GameData struct kept persistent between requests by Rocket.
+ * "config" is the channel config kept persistent between requests by Rocket.
+ * "tech_info" are the query parameters (civ, version, format). They might be None.
+ * "format" is the requested output format.
+ * Only accepts the request if the Nightbot headers are present.
+ */
+#[get("/tech/<name>?<tech_info>")]
+pub fn tech_with_info(data_lock: State<RwLock<GameData>>, config: State<ChannelConfig>, name: String, tech_info: TechInfo, format: OutputFormat, nightbot_headers: NightbotHeaderFields) -> Output {
 	let data = data_lock.read().unwrap();
-	let tech_info = if let Some(tech_info) = fetch_tech_data(&data, &name) {
-		tech_info
-	} else {
-		String::from("That tech does not exist.")
+	let templates = config.templates(&nightbot_headers);
+	let tech_info = match config.game_version(&nightbot_headers, tech_info.version.as_ref()) {
+		Ok(version) => fetch_tech_data(&data, &name, tech_info.civ.as_ref(), version, &templates)
+			.unwrap_or_else(|| Err(util::with_suggestions("That tech does not exist.", &data.tech_suggestions(&name)))),
+		Err(_) => Err(String::from("That game version does not exist."))
 	};
 	
-	util::create_response(tech_info, &nightbot_headers)
+	util::create_output(tech_info, format, &nightbot_headers)
 }
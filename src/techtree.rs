@@ -0,0 +1,99 @@
+use std::sync::RwLock;
+
+use rocket::State;
+
+use util::{
+	self,
+	NightbotHeaderFields
+};
+use config::ChannelConfig;
+use data::{
+	GameData,
+	GameVersion,
+	Entity
+};
+
+/*
+ * Additional information passed as query parameters.
+ */
+#[derive(FromForm)]
+pub struct TechtreeInfo {
+	version: Option<String>
+}
+
+/*
+ * Lists the units and techs of a building the specified civ is missing.
+ * Unique units and techs are left out because only one civ has them anyway.
+ */
+fn fetch_techtree<S, T>(data: &GameData, civ_name: S, building_name: T, version: Option<GameVersion>) -> String where S: AsRef<str>, T: AsRef<str> {
+	let civ = if let Some(civ) = data.civ_by_name(&civ_name) {
+		civ
+	} else {
+		return util::with_suggestions("That civ does not exist.", &data.civ_suggestions(&civ_name));
+	};
+	let building = if let Some(building) = data.building_by_name(&building_name) {
+		building
+	} else {
+		return util::with_suggestions("That building does not exist.", &data.building_suggestions(&building_name));
+	};
+	let correction = format!("{}{}", data.correction(civ_name.as_ref(), &civ.name), data.correction(building_name.as_ref(), &building.name));
+	let not_in_version = util::not_in_version(&building.name, building.version(), version)
+		.or_else(|| util::not_in_version(&civ.name, civ.version(), version));
+	
+	if let Some(not_in_version) = not_in_version {
+		return format!("{}{}", correction, not_in_version);
+	}
+	
+	if let Some((false, civ_name)) = building.available_to(&civ.name) {
+		return format!("{}{} cannot build the {}.", correction, civ_name, building.name);
+	}
+	
+	let building_type = util::shrink(&building.name);
+	let units = data.units.iter().filter(|unit| util::shrink(&unit.type_name).eq_ignore_ascii_case(&building_type)).map(Entity::Unit);
+	let techs = data.techs.iter().filter(|tech| util::shrink(&tech.type_name).eq_ignore_ascii_case(&building_type)).map(Entity::Tech);
+	let missing = units.chain(techs)
+		.filter(|entity| version.map_or(true, |version| version.includes(entity.version())))
+		.filter(|entity| match entity.available_to(&civ.name) {
+			Some((false, _)) => true,
+			_ => false
+		})
+		.map(|entity| format!("no {}", entity.name()))
+		.collect::<Vec<_>>();
+	
+	if missing.is_empty() {
+		format!("{}{} {}: full tech tree.", correction, civ.name, building.name)
+	} else {
+		format!("{}{} {}: {}.", correction, civ.name, building.name, missing.join(", "))
+	}
+}
+
+/*
+ * Request handler for the techtree resource.
+ * Lists what the specified civ is missing in a building.
+ * "data_lock" is the GameData struct kept persistent between requests by Rocket.
+ * "config" is the channel config kept persistent between requests by Rocket.
+ * Only accepts the request if the Nightbot headers are present.
+ */
+#[get("/techtree/<civ>/<building>")]
+pub fn techtree(data_lock: State<RwLock<GameData>>, config: State<ChannelConfig>, civ: String, building: String, nightbot_headers: NightbotHeaderFields) -> String {
+	techtree_with_info(data_lock, config, civ, building, TechtreeInfo { version: None }, nightbot_headers)
+}
+
+/*
+ * Request handler for the techtree resource with additional information.
+ * Lists what the specified civ is missing in a building of the requested game version.
+ * "data_lock" is the GameData struct kept persistent between requests by Rocket.
+ * "config" is the channel config kept persistent between requests by Rocket.
+ * "techtree_info" are the query parameters (version). They might be None.
+ * Only accepts the request if the Nightbot headers are present.
+ */
+#[get("/techtree/<civ>/<building>?<techtree_info>")]
+pub fn techtree_with_info(data_lock: State<RwLock<GameData>>, config: State<ChannelConfig>, civ: String, building: String, techtree_info: TechtreeInfo, nightbot_headers: NightbotHeaderFields) -> String {
+	let data = data_lock.read().unwrap();
+	let techtree_info = match config.game_version(&nightbot_headers, techtree_info.version.as_ref()) {
+		Ok(version) => fetch_techtree(&data, &civ, &building, version),
+		Err(_) => String::from("That game version does not exist.")
+	};
+	
+	util::create_response(techtree_info, &nightbot_headers)
+}
@@ -0,0 +1,115 @@
+use std::collections::HashMap;
+
+/*
+ * Answer templates of a channel.
+ * Templates contain named placeholders like "{name}" which are replaced by the values of an answer.
+ * Every answer has a built-in default template, channels can override it in the channel config.
+ */
+pub struct Templates<'a> {
+	overrides: Option<&'a HashMap<String, String>>
+}
+
+impl<'a> Templates<'a> {
+	/*
+	 * Creates the templates of a channel from its overrides, if it has any.
+	 */
+	pub fn new(overrides: Option<&'a HashMap<String, String>>) -> Self {
+		Templates {
+			overrides: overrides
+		}
+	}
+	
+	/*
+	 * Fills in the template with the given key, or the default template if the channel doesn't override it.
+	 */
+	pub fn render(&self, key: &str, default: &str, values: &[(&str, String)]) -> String {
+		let template = self.overrides
+			.and_then(|overrides| overrides.get(key))
+			.map(String::as_str)
+			.unwrap_or(default);
+		
+		fill(template, values)
+	}
+}
+
+/*
+ * Replaces the placeholders in a template by their values.
+ * Braces which are not part of a known placeholder are kept as they are.
+ */
+fn fill(template: &str, values: &[(&str, String)]) -> String {
+	let mut filled = String::with_capacity(template.len());
+	let mut rest = template;
+	
+	while let Some(start) = rest.find('{') {
+		filled.push_str(&rest[.. start]);
+		rest = &rest[start ..];
+		
+		let value = rest.find('}').and_then(|end| {
+			values.iter()
+				.find(|(placeholder, _)| *placeholder == &rest[1 .. end])
+				.map(|(_, value)| (end, value))
+		});
+		
+		if let Some((end, value)) = value {
+			filled.push_str(value);
+			rest = &rest[end + 1 ..];
+		} else {
+			filled.push('{');
+			rest = &rest[1 ..];
+		}
+	}
+	
+	filled.push_str(rest);
+	
+	filled
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	
+	fn values() -> Vec<(&'static str, String)> {
+		vec![("name", String::from("Paladin")), ("hp", String::from("160"))]
+	}
+	
+	#[test]
+	fn fill_placeholders() {
+		assert_eq!(fill("{name} has {hp} HP.", &values()), "Paladin has 160 HP.");
+		assert_eq!(fill("{name}{name}", &values()), "PaladinPaladin");
+	}
+	
+	#[test]
+	fn fill_unknown_placeholder() {
+		assert_eq!(fill("{name} has {armor} armor.", &values()), "Paladin has {armor} armor.");
+	}
+	
+	#[test]
+	fn fill_unmatched_braces() {
+		assert_eq!(fill("{name has {hp} HP", &values()), "{name has 160 HP");
+		assert_eq!(fill("name} has {hp", &values()), "name} has {hp");
+		assert_eq!(fill("{{name}}", &values()), "{Paladin}");
+	}
+	
+	#[test]
+	fn render_default_without_channel() {
+		let templates = Templates::new(None);
+		
+		assert_eq!(templates.render("unit", "{name}: {hp} HP", &values()), "Paladin: 160 HP");
+	}
+	
+	#[test]
+	fn render_default_without_override() {
+		let overrides = [(String::from("tech"), String::from("{name}!"))].iter().cloned().collect::<HashMap<_, _>>();
+		let templates = Templates::new(Some(&overrides));
+		
+		assert_eq!(templates.render("unit", "{name}: {hp} HP", &values()), "Paladin: 160 HP");
+	}
+	
+	#[test]
+	fn render_override() {
+		let overrides = [(String::from("unit"), String::from("{hp} HP for {name}"))].iter().cloned().collect::<HashMap<_, _>>();
+		let templates = Templates::new(Some(&overrides));
+		
+		assert_eq!(templates.render("unit", "{name}: {hp} HP", &values()), "160 HP for Paladin");
+	}
+}
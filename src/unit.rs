@@ -4,28 +4,204 @@ use rocket::State;
 
 use util::{
 	self,
-	NightbotHeaderFields
+	NightbotHeaderFields,
+	OutputFormat,
+	Output
 };
-use data::GameData;
+use data::{
+	GameData,
+	GameVersion,
+	Unit,
+	UnitStats
+};
+use config::ChannelConfig;
+use template::Templates;
+
+/*
+ * Default answer templates. Channels can override them with the keys "unit", "unit_ranged" and "unit_upgraded".
+ */
+const UNIT_TEMPLATE: &'static str = "{name} ({type}{version}) costs {cost}, is available in {age}, takes {time} to create, has {hp} HP, {attack} attack and {armor} armor.";
+const UNIT_RANGED_TEMPLATE: &'static str = "{name} ({type}{version}) costs {cost}, is available in {age}, takes {time} to create, has {hp} HP, {attack} attack, {armor} armor and {range} range.";
+const UNIT_UPGRADED_TEMPLATE: &'static str = "{name} ({type}{version}) fully upgraded{for_civ}: {stats}.";
+
+/*
+ * Possible query parameters passed to the unit resource.
+ */
+#[derive(FromForm)]
+pub struct UnitInfo {
+	civ: Option<String>,
+	version: Option<String>,
+	upgraded: Option<bool>,
+	/* Read by the OutputFormat guard */
+	#[allow(dead_code)]
+	format: Option<String>
+}
+
+/*
+ * JSON payload of the unit resource.
+ */
+#[derive(Serialize)]
+struct UnitPayload<'a> {
+	unit: &'a Unit,
+	civ: Option<&'a str>,
+	available: bool,
+	civ_notes: Vec<String>,
+	upgraded_stats: Option<UnitStats>
+}
+
+/*
+ * Describes the stats of a fully upgraded unit, e.g. "180 HP, 18 attack, 7/9 armor".
+ */
+fn describe_stats(stats: &UnitStats) -> String {
+	let mut parts = Vec::new();
+	
+	if let Some(hp) = stats.hp {
+		parts.push(format!("{} HP", hp.round()));
+	}
+	if let Some(attack) = stats.attack {
+		parts.push(format!("{} attack", attack));
+	}
+	if let Some((melee, pierce)) = stats.armor {
+		parts.push(format!("{}/{} armor", melee, pierce));
+	}
+	if let Some(range) = stats.range {
+		parts.push(format!("{} range", range));
+	}
+	if let Some(line_of_sight) = stats.line_of_sight {
+		parts.push(format!("{} line of sight", line_of_sight));
+	}
+	
+	parts.join(", ")
+}
+
+/*
+ * Fetches the data for the specified unit name.
+ * If a civ is given, the civ specific notes are appended.
+ * If a game version is given, only things that are part of it are considered.
+ * If "upgraded" is set or the name starts with "FU", the stats with all techs (the civ can research) are returned instead.
+ * "uu" is resolved to the unique unit of the civ.
+ * The chat text is rendered from the channel's templates.
+ * Returns the chat text and the JSON payload, or the answer if the unit could not be looked up.
+ */
+fn fetch_unit_data<'a, S>(data: &'a GameData, name: S, civ: Option<&String>, version: Option<GameVersion>, upgraded: bool, templates: &Templates) -> Result<(String, UnitPayload<'a>), String> where S: AsRef<str> {
+	let civ = if let Some(civ) = civ {
+		if let Some(civ) = data.civ_by_name(civ) {
+			Some(civ)
+		} else {
+			return Err(util::with_suggestions("That civ does not exist.", &data.civ_suggestions(civ)));
+		}
+	} else {
+		None
+	};
+	/* "FU paladin" asks for the fully upgraded stats */
+	let mut words = name.as_ref().trim().splitn(2, ' ');
+	let (name, upgraded) = match (words.next(), words.next()) {
+		(Some(prefix), Some(rest)) if prefix.eq_ignore_ascii_case("fu") => (rest.trim().to_string(), true),
+		_ => (name.as_ref().to_string(), upgraded)
+	};
+	/* "uu" is the unique unit of the civ */
+	let name = match civ {
+		Some(civ) if name.eq_ignore_ascii_case("uu") => civ.unique_units().into_iter().next().unwrap_or(name),
+		_ => name
+	};
+	let unit = if let Some(unit) = data.unit_by_name(&name) {
+		unit
+	} else {
+		return Err(util::with_suggestions("That unit does not exist.", &data.unit_suggestions(&name)));
+	};
+	let correction = data.correction(&name, &unit.name);
+	let not_in_version = util::not_in_version(&unit.name, unit.version(), version)
+		.or_else(|| civ.and_then(|civ| util::not_in_version(&civ.name, civ.version(), version)));
+	
+	if let Some(not_in_version) = not_in_version {
+		return Err(format!("{}{}", correction, not_in_version));
+	}
+	
+	let mut payload = UnitPayload {
+		unit: unit,
+		civ: civ.map(|civ| civ.name.as_str()),
+		available: true,
+		civ_notes: Vec::new(),
+		upgraded_stats: None
+	};
+	
+	if let Some(civ) = civ {
+		if let Some((false, civ_name)) = unit.available_to(&civ.name) {
+			payload.available = false;
+			
+			return Ok((format!("{}{} cannot train {}.", correction, civ_name, unit.name), payload));
+		}
+	}
+	
+	let version_name = unit.version().map(|version| format!(", {}", version.name())).unwrap_or_default();
+	let mut values = vec![
+		("name", unit.name.clone()),
+		("type", unit.type_name.clone()),
+		("version", version_name),
+		("cost", unit.cost.to_string()),
+		("age", unit.age.to_string()),
+		("time", unit.time.clone()),
+		("hp", unit.hp.clone()),
+		("attack", unit.attack.clone()),
+		("armor", unit.armor.clone()),
+		("range", unit.range.clone())
+	];
+	let unit_info = if upgraded {
+		let stats = data.upgraded_stats(unit, civ, version);
+		
+		payload.upgraded_stats = Some(stats);
+		values.push(("for_civ", civ.map(|civ| format!(" for {}", civ.name)).unwrap_or_default()));
+		values.push(("stats", describe_stats(&stats)));
+		templates.render("unit_upgraded", UNIT_UPGRADED_TEMPLATE, &values)
+	} else if !unit.range.is_empty() && unit.range != "-" {
+		templates.render("unit_ranged", UNIT_RANGED_TEMPLATE, &values)
+	} else {
+		templates.render("unit", UNIT_TEMPLATE, &values)
+	};
+	let civ_notes = civ.map(|civ| unit.civ_notes(&civ.name)).unwrap_or_else(Vec::new);
+	let unit_info = if civ_notes.is_empty() {
+		format!("{}{}", correction, unit_info)
+	} else {
+		format!("{}{} {}.", correction, unit_info, civ_notes.join("; "))
+	};
+	
+	payload.civ_notes = civ_notes;
+	
+	Ok((unit_info, payload))
+}
 
 /*
  * Request handler for the unit resource.
  * Grabs the specified unit from the game data and processes it.
  * "data_lock" is the GameData struct kept persistent between requests by Rocket.
+ * "config" is the channel config kept persistent between requests by Rocket.
+ * "format" is the requested output format.
  * Only accepts the request if the Nightbot headers are present.
  */
 #[get("/unit/<name>")]
-pub fn unit(data_lock: State<RwLock<GameData>>, name: String, nightbot_headers: NightbotHeaderFields) -> String {
+pub fn unit(data_lock: State<RwLock<GameData>>, config: State<ChannelConfig>, name: String, format: OutputFormat, nightbot_headers: NightbotHeaderFields) -> Output {
+	let unit_info = UnitInfo { civ: None, version: None, upgraded: None, format: None };
+	
+	unit_with_info(data_lock, config, name, unit_info, format, nightbot_headers)
+}
+
+/*
+ * Request handler for the unit resource.
+ * Grabs the specified unit from the game data and processes it.
+ * "data_lock" is the GameData struct kept persistent between requests by Rocket.
+ * "config" is the channel config kept persistent between requests by Rocket.
+ * "unit_info" are the query parameters (civ, version, upgraded, format). They might be None.
+ * "format" is the requested output format.
+ * Only accepts the request if the Nightbot headers are present.
+ */
+#[get("/unit/<name>?<unit_info>")]
+pub fn unit_with_info(data_lock: State<RwLock<GameData>>, config: State<ChannelConfig>, name: String, unit_info: UnitInfo, format: OutputFormat, nightbot_headers: NightbotHeaderFields) -> Output {
 	let data = data_lock.read().unwrap();
-	let unit_info = if let Some(unit) = data.unit_by_name(&name) {
-		if !unit.range.is_empty() && unit.range != "-" {
-			format!("{} ({}) costs {}, takes {} to create, has {} HP, {} attack, {} armor and {} range.", unit.name, unit.type_name, unit.cost, unit.time, unit.hp, unit.attack, unit.armor, unit.range)
-		} else {
-			format!("{} ({}) costs {}, takes {} to create, has {} HP, {} attack and {} armor.", unit.name, unit.type_name, unit.cost, unit.time, unit.hp, unit.attack, unit.armor)
-		}
-	} else {
-		String::from("That unit does not exist.")
+	let templates = config.templates(&nightbot_headers);
+	let unit_info = match config.game_version(&nightbot_headers, unit_info.version.as_ref()) {
+		Ok(version) => fetch_unit_data(&data, &name, unit_info.civ.as_ref(), version, unit_info.upgraded.unwrap_or(false), &templates),
+		Err(_) => Err(String::from("That game version does not exist."))
 	};
 	
-	util::create_response(unit_info, &nightbot_headers)
+	util::create_output(unit_info, format, &nightbot_headers)
 }
@@ -0,0 +1,131 @@
+use std::sync::RwLock;
+use std::time::Duration;
+
+use rocket::State;
+
+use util::{
+	self,
+	NightbotHeaderFields
+};
+use data::{
+	GameData,
+	GameVersion,
+	Cost
+};
+use config::ChannelConfig;
+
+/*
+ * Possible query parameters passed to the upgrade cost resource.
+ */
+#[derive(FromForm)]
+pub struct UpgradeCostInfo {
+	civ: Option<String>,
+	version: Option<String>
+}
+
+/*
+ * Sums up cost and research time of all techs needed to fully upgrade the specified unit.
+ * If a civ is given, its tech tree and civ specific costs are used.
+ */
+fn fetch_upgrade_cost<S>(data: &GameData, name: S, civ: Option<&String>, version: Option<GameVersion>) -> String where S: AsRef<str> {
+	let unit = if let Some(unit) = data.unit_by_name(&name) {
+		unit
+	} else {
+		return util::with_suggestions("That unit does not exist.", &data.unit_suggestions(&name));
+	};
+	let civ = if let Some(civ) = civ {
+		if let Some(civ) = data.civ_by_name(civ) {
+			Some(civ)
+		} else {
+			return util::with_suggestions("That civ does not exist.", &data.civ_suggestions(civ));
+		}
+	} else {
+		None
+	};
+	let correction = data.correction(name.as_ref(), &unit.name);
+	let not_in_version = util::not_in_version(&unit.name, unit.version(), version)
+		.or_else(|| civ.and_then(|civ| util::not_in_version(&civ.name, civ.version(), version)));
+	
+	if let Some(not_in_version) = not_in_version {
+		return format!("{}{}", correction, not_in_version);
+	}
+	
+	if let Some(civ) = civ {
+		/* The civ needs every upgrade of the line up to the unit */
+		let line = data.unit_line(unit);
+		let position = line.iter().position(|step| step.unit.name == unit.name).unwrap_or(0);
+		let trainable = line[..position + 1].iter().all(|step| match step.tech.map_or_else(|| step.unit.available_to(&civ.name), |tech| tech.available_to(&civ.name)) {
+			Some((false, _)) => false,
+			_ => true
+		});
+		
+		if !trainable {
+			return format!("{}{} cannot train {}.", correction, civ.name, unit.name);
+		}
+	}
+	
+	let techs = data.unit_upgrades(unit, civ)
+		.into_iter()
+		.filter(|tech| version.map_or(true, |version| version.includes(tech.version())))
+		.collect::<Vec<_>>();
+	let (available, missing): (Vec<_>, Vec<_>) = techs.into_iter()
+		.partition(|tech| civ.and_then(|civ| tech.available_to(&civ.name)).map_or(true, |(available, _)| available));
+	
+	if available.is_empty() {
+		return format!("{}{} has no upgrades.", correction, unit.name);
+	}
+	
+	let mut cost = Cost::default();
+	let mut time = Duration::from_secs(0);
+	
+	for tech in &available {
+		cost = cost + civ.map_or_else(|| tech.cost.clone(), |civ| tech.civ_cost(&civ.name));
+		time += civ.map_or_else(|| tech.research_time(), |civ| tech.civ_research_time(&civ.name)).unwrap_or_else(|| Duration::from_secs(0));
+	}
+	
+	let names = available.iter().map(|tech| tech.name.as_str()).collect::<Vec<_>>();
+	let civ_name = civ.map(|civ| format!(" for {}", civ.name)).unwrap_or_default();
+	let upgrade_info = format!("{}Fully upgrading {}{} costs {} and takes {} of research: {}.", correction, unit.name, civ_name, cost, util::format_duration(time), names.join(", "));
+	
+	match civ {
+		Some(civ) if !missing.is_empty() => {
+			let missing = missing.iter().map(|tech| tech.name.as_str()).collect::<Vec<_>>();
+			
+			format!("{} {} lack {}.", upgrade_info, civ.name, missing.join(", "))
+		},
+		_ => upgrade_info
+	}
+}
+
+/*
+ * Request handler for the upgrade cost resource.
+ * Sums up the cost of all techs needed to fully upgrade the specified unit.
+ * "data_lock" is the GameData struct kept persistent between requests by Rocket.
+ * "config" is the channel config kept persistent between requests by Rocket.
+ * Only accepts the request if the Nightbot headers are present.
+ */
+#[get("/upgradecost/<name>")]
+pub fn upgrade_cost(data_lock: State<RwLock<GameData>>, config: State<ChannelConfig>, name: String, nightbot_headers: NightbotHeaderFields) -> String {
+	let upgrade_cost_info = UpgradeCostInfo { civ: None, version: None };
+	
+	upgrade_cost_with_info(data_lock, config, name, upgrade_cost_info, nightbot_headers)
+}
+
+/*
+ * Request handler for the upgrade cost resource.
+ * Sums up the cost of all techs needed to fully upgrade the specified unit.
+ * "data_lock" is the GameData struct kept persistent between requests by Rocket.
+ * "config" is the channel config kept persistent between requests by Rocket.
+ * "upgrade_cost_info" are the query parameters (civ, version). They might be None.
+ * Only accepts the request if the Nightbot headers are present.
+ */
+#[get("/upgradecost/<name>?<upgrade_cost_info>")]
+pub fn upgrade_cost_with_info(data_lock: State<RwLock<GameData>>, config: State<ChannelConfig>, name: String, upgrade_cost_info: UpgradeCostInfo, nightbot_headers: NightbotHeaderFields) -> String {
+	let data = data_lock.read().unwrap();
+	let upgrade_cost_info = match config.game_version(&nightbot_headers, upgrade_cost_info.version.as_ref()) {
+		Ok(version) => fetch_upgrade_cost(&data, &name, upgrade_cost_info.civ.as_ref(), version),
+		Err(_) => String::from("That game version does not exist.")
+	};
+	
+	util::create_response(upgrade_cost_info, &nightbot_headers)
+}
@@ -1,4 +1,5 @@
 use std::collections::HashMap;
+use std::time::Duration;
 
 use rocket::{
 	Request,
@@ -6,11 +7,23 @@ use rocket::{
 		FromRequest,
 		Outcome as RequestOutcome
 	},
+	response::{
+		self,
+		Responder,
+		content
+	},
 	outcome::Outcome,
-	http::Status
+	http::{
+		Status,
+		MediaType
+	}
 };
+use serde::Serialize;
+use serde_json;
 use url::form_urlencoded;
 
+use data::GameVersion;
+
 /*
  * The header fields Nightbot passes with each request.
  */
@@ -49,6 +62,61 @@ impl<'a, 'r> FromRequest<'a, 'r> for NightbotHeaderFields {
 	}
 }
 
+/*
+ * Format of the answer of a resource.
+ * Chat text is the default, JSON is used if requested by "Accept: application/json" or "?format=json".
+ * Rocket's query forms reject unknown parameters, so every query struct of a resource with JSON output needs an unused "format" field.
+ */
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum OutputFormat {
+	Text,
+	Json
+}
+
+impl<'a, 'r> FromRequest<'a, 'r> for OutputFormat {
+	type Error = ();
+	
+	fn from_request(request: &'a Request<'r>) -> RequestOutcome<Self, Self::Error> {
+		let json_requested = request.uri().query()
+			.map(|query| form_urlencoded::parse(query.as_bytes()).any(|(key, value)| key == "format" && value.eq_ignore_ascii_case("json")))
+			.unwrap_or(false);
+		let json_accepted = request.accept()
+			.map(|accept| *accept.preferred().media_type() == MediaType::JSON)
+			.unwrap_or(false);
+		
+		if json_requested || json_accepted {
+			Outcome::Success(OutputFormat::Json)
+		} else {
+			Outcome::Success(OutputFormat::Text)
+		}
+	}
+}
+
+/*
+ * Answer of a resource in one of the output formats.
+ */
+pub enum Output {
+	Text(String),
+	Json(String)
+}
+
+impl<'r> Responder<'r> for Output {
+	fn respond_to(self, request: &Request) -> response::Result<'r> {
+		match self {
+			Output::Text(text) => text.respond_to(request),
+			Output::Json(json) => content::Json(json).respond_to(request)
+		}
+	}
+}
+
+/*
+ * JSON payload of answers that failed, e.g. because a name does not exist.
+ */
+#[derive(Serialize)]
+struct ErrorPayload {
+	error: String
+}
+
 /*
  * Parses the the Nightbot user header fields.
  */
@@ -86,6 +154,19 @@ pub fn create_response<S>(response: S, nightbot_headers: &NightbotHeaderFields)
 	format!("{}{}", mention, response.as_ref())
 }
 
+/*
+ * Creates the output of a resource in the requested format.
+ * Successful answers consist of the chat text and a payload which is serialized for JSON output.
+ * Failed answers only have a text, which JSON output wraps as {"error": "..."}.
+ */
+pub fn create_output<T>(answer: Result<(String, T), String>, format: OutputFormat, nightbot_headers: &NightbotHeaderFields) -> Output where T: Serialize {
+	match (format, answer) {
+		(OutputFormat::Text, Ok((text, _))) | (OutputFormat::Text, Err(text)) => Output::Text(create_response(text, nightbot_headers)),
+		(OutputFormat::Json, Ok((_, payload))) => Output::Json(serde_json::to_string(&payload).expect("Serializable payload")),
+		(OutputFormat::Json, Err(error)) => Output::Json(serde_json::to_string(&ErrorPayload { error: error }).expect("Serializable payload"))
+	}
+}
+
 /*
  * Removes spaces, dashes and leading s' from a string.
  */
@@ -98,3 +179,163 @@ pub fn shrink(s: &str) -> String {
 	
 	s
 }
+
+/*
+ * Splits an HTML list ("<ul><li>...</li></ul>") into its items and removes any remaining tags.
+ */
+pub fn strip_html_list(s: &str) -> Vec<String> {
+	s.split("<li>")
+		.map(|item| {
+			let mut text = String::new();
+			let mut in_tag = false;
+			
+			for c in item.chars() {
+				match c {
+					'<' => in_tag = true,
+					'>' => in_tag = false,
+					_ if !in_tag => text.push(c),
+					_ => ()
+				}
+			}
+			
+			text.trim().trim_right_matches('.').to_string()
+		})
+		.filter(|item| !item.is_empty())
+		.collect()
+}
+
+/*
+ * Calculates the Levenshtein distance between two strings.
+ */
+pub fn edit_distance(a: &str, b: &str) -> usize {
+	let b = b.chars().collect::<Vec<_>>();
+	let mut distances = (0 ..= b.len()).collect::<Vec<_>>();
+	
+	for (i, ca) in a.chars().enumerate() {
+		let mut previous = distances[0];
+		
+		distances[0] = i + 1;
+		
+		for (j, cb) in b.iter().enumerate() {
+			let substitution = if ca == *cb { previous } else { previous + 1 };
+			
+			previous = distances[j + 1];
+			distances[j + 1] = substitution.min(distances[j] + 1).min(previous + 1);
+		}
+	}
+	
+	distances[b.len()]
+}
+
+/*
+ * Rates how well a name matches what the user typed, lower is better.
+ * 0 means equal (after shrinking), 1 means the name starts with the query, everything above is a typo.
+ * Returns None if the name is not similar at all.
+ */
+pub fn match_score(query: &str, name: &str) -> Option<usize> {
+	let query = shrink(query).to_lowercase();
+	let name = shrink(name).to_lowercase();
+	let query_len = query.chars().count();
+	
+	if query == name {
+		Some(0)
+	} else if query_len >= 3 && name.starts_with(&query) {
+		Some(1)
+	} else if query_len >= 3 {
+		let distance = edit_distance(&query, &name);
+		
+		if distance <= (query_len / 4).max(1) {
+			Some(distance + 1)
+		} else {
+			None
+		}
+	} else {
+		None
+	}
+}
+
+/*
+ * Creates the "Did you mean ...?" prefix if the found name differs from what the user typed.
+ */
+pub fn correction(query: &str, name: &str) -> String {
+	if match_score(query, name) == Some(0) {
+		String::new()
+	} else {
+		format!("Did you mean {}? ", name)
+	}
+}
+
+/*
+ * Appends the near-misses to an answer, if there are any.
+ */
+pub fn with_suggestions<S>(answer: S, suggestions: &[&str]) -> String where S: AsRef<str> {
+	let answer = answer.as_ref();
+	
+	match suggestions.split_last() {
+		None => answer.to_string(),
+		Some((last, rest)) if rest.is_empty() => format!("{} Did you mean {}?", answer, last),
+		Some((last, rest)) => format!("{} Did you mean {} or {}?", answer, rest.join(", "), last)
+	}
+}
+
+/*
+ * Creates the answer for something that is not part of the requested game version.
+ * Returns None if there is no version filter or it is part of the version.
+ */
+pub fn not_in_version(name: &str, introduced: Option<GameVersion>, version: Option<GameVersion>) -> Option<String> {
+	let version = version?;
+	
+	if version.includes(introduced) {
+		None
+	} else {
+		Some(format!("{} is not part of {}.", name, version.name()))
+	}
+}
+
+/*
+ * Formats a duration like the game data does ("1:05").
+ */
+pub fn format_duration(duration: Duration) -> String {
+	let seconds = duration.as_secs() + if duration.subsec_nanos() >= 500_000_000 { 1 } else { 0 };
+	
+	format!("{}:{:02}", seconds / 60, seconds % 60)
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	
+	#[test]
+	fn edit_distance_typos() {
+		assert_eq!(edit_distance("", ""), 0);
+		assert_eq!(edit_distance("paladin", "paladin"), 0);
+		assert_eq!(edit_distance("paladn", "paladin"), 1);
+		assert_eq!(edit_distance("plaadin", "paladin"), 2);
+		assert_eq!(edit_distance("kitten", "sitting"), 3);
+		assert_eq!(edit_distance("", "abc"), 3);
+	}
+	
+	#[test]
+	fn match_score_equal() {
+		assert_eq!(match_score("Paladin", "paladin"), Some(0));
+		assert_eq!(match_score("man at arms", "Man-at-Arms"), Some(0));
+	}
+	
+	#[test]
+	fn match_score_prefix() {
+		assert_eq!(match_score("pala", "Paladin"), Some(1));
+		assert_eq!(match_score("pa", "Paladin"), None);
+	}
+	
+	#[test]
+	fn match_score_typo() {
+		assert_eq!(match_score("paladn", "Paladin"), Some(2));
+		assert_eq!(match_score("arbalester", "Arbalest"), Some(3));
+	}
+	
+	#[test]
+	fn match_score_no_match() {
+		assert_eq!(match_score("paladin", "Arbalest"), None);
+		assert_eq!(match_score("xyz", "Paladin"), None);
+	}
+}
@@ -1,4 +1,5 @@
 use std::collections::HashMap;
+use std::sync::Mutex;
 use std::time::{
 	Instant,
 	Duration
@@ -8,16 +9,107 @@ use request;
 use cookie::CookieJar;
 use table_extract::Table;
 
+use coalesce::Coalescer;
+use cache::Cache;
+
+/*
+ * Reasons why a request to Voobly failed.
+ * Failures of the bot or Voobly carry a description of the cause for the log.
+ */
+#[derive(Clone, Debug)]
+pub enum VooblyError {
+	/* Voobly could not be reached or answered with an error */
+	Unavailable(String),
+	/* Credentials are missing or logging in failed */
+	NotLoggedIn(String),
+	UnknownUser,
+	NoMatches
+}
+
+impl VooblyError {
+	/*
+	 * Answer for the chat.
+	 */
+	pub fn answer(&self) -> &'static str {
+		match *self {
+			VooblyError::Unavailable(_) => "Voobly is down.",
+			VooblyError::NotLoggedIn(_) => "Bot is not logged in to Voobly.",
+			VooblyError::UnknownUser => "That user doesn't exist.",
+			VooblyError::NoMatches => "No matches found."
+		}
+	}
+	
+	/*
+	 * Logs the cause of failures of the bot or Voobly.
+	 * Unknown users and empty match histories are expected and not logged.
+	 */
+	pub fn log(&self) {
+		match *self {
+			VooblyError::Unavailable(ref cause) => eprintln!("Voobly is unavailable: {}", cause),
+			VooblyError::NotLoggedIn(ref cause) => eprintln!("Not logged in to Voobly: {}", cause),
+			VooblyError::UnknownUser | VooblyError::NoMatches => ()
+		}
+	}
+}
+
+/*
+ * Login state of the Voobly session.
+ */
+#[derive(Default)]
+struct Session {
+	/* Cookies of the logged in session, None if not logged in */
+	cookies: Option<CookieJar>,
+	/* Incremented on every login, so requests can tell if the session was replaced while they were fetching */
+	generation: u64,
+	login_failures: u32,
+	/* No login is attempted before this point in time after failed logins */
+	login_retry: Option<Instant>
+}
+
+impl Session {
+	const LOGIN_BACKOFF: Duration = Duration::from_secs(30);
+	const MAX_LOGIN_BACKOFF: Duration = Duration::from_secs(30 * 60);
+	
+	/*
+	 * Resets the backoff once a page could be fetched with the session.
+	 */
+	fn login_succeeded(&mut self) {
+		self.login_failures = 0;
+		self.login_retry = None;
+	}
+	
+	/*
+	 * Drops the session and doubles the time until the next login attempt, up to a maximum.
+	 */
+	fn login_failed(&mut self) {
+		let backoff = Self::LOGIN_BACKOFF * 2u32.pow(self.login_failures.min(6));
+		
+		self.cookies = None;
+		self.login_failures += 1;
+		self.login_retry = Some(Instant::now() + backoff.min(Self::MAX_LOGIN_BACKOFF));
+	}
+}
+
 /*
  * The Voobly API struct.
+ * Every cache is locked on its own, so requests only wait for each other while accessing the same cache.
+ * Concurrent fetches of the same user are coalesced into one request to Voobly.
+ * Size and duration of the caches can be set with the environment variables "ID_CACHE_*", "UNKNOWN_USER_CACHE_*", "ELO_CACHE_*" and "MATCH_CACHE_*".
  */
 pub struct VooblyApi {
 	key: String,
 	username: String,
 	password: String,
-	id_cache: HashMap<String, (String, String)>,
-	elo_cache: HashMap<(String, String), (String, Instant)>,
-	match_cache: HashMap<String, (Table, Instant)>
+	id_cache: Mutex<Cache<String, (String, String)>>,
+	/* Names Voobly didn't find, so repeated requests for them don't reach Voobly */
+	unknown_user_cache: Mutex<Cache<String, ()>>,
+	elo_cache: Mutex<Cache<(String, String), String>>,
+	match_cache: Mutex<Cache<(String, u16), Table>>,
+	/* Locked while logging in, so concurrent requests wait for the same login */
+	session: Mutex<Session>,
+	user_info_fetches: Coalescer<String, Result<(String, String), VooblyError>>,
+	elo_fetches: Coalescer<(String, String), Result<Option<String>, VooblyError>>,
+	match_fetches: Coalescer<(String, u16), Result<Table, VooblyError>>
 }
 
 impl VooblyApi {
@@ -26,7 +118,13 @@ impl VooblyApi {
 	pub const DM_1_V_1: &'static str = "163";
 	pub const DM_TG: &'static str = "162";
 	
+	const ID_CACHE_SIZE: usize = 1000;
+	const ID_CACHE_DURATION: Duration = Duration::from_secs(24 * 60 * 60);
+	const UNKNOWN_USER_CACHE_SIZE: usize = 1000;
+	const UNKNOWN_USER_CACHE_DURATION: Duration = Duration::from_secs(10 * 60);
+	const ELO_CACHE_SIZE: usize = 1000;
 	const ELO_CACHE_DURATION: Duration = Duration::from_secs(180);
+	const MATCH_CACHE_SIZE: usize = 100;
 	const MATCH_CACHE_DURATION: Duration = Duration::from_secs(180);
 	
 	/*
@@ -37,95 +135,224 @@ impl VooblyApi {
 			key: key.into(),
 			username: username.into(),
 			password: password.into(),
-			id_cache: HashMap::new(),
-			elo_cache: HashMap::new(),
-			match_cache: HashMap::new()
+			id_cache: Mutex::new(Cache::from_env("ID", Self::ID_CACHE_SIZE, Self::ID_CACHE_DURATION)),
+			unknown_user_cache: Mutex::new(Cache::from_env("UNKNOWN_USER", Self::UNKNOWN_USER_CACHE_SIZE, Self::UNKNOWN_USER_CACHE_DURATION)),
+			elo_cache: Mutex::new(Cache::from_env("ELO", Self::ELO_CACHE_SIZE, Self::ELO_CACHE_DURATION)),
+			match_cache: Mutex::new(Cache::from_env("MATCH", Self::MATCH_CACHE_SIZE, Self::MATCH_CACHE_DURATION)),
+			session: Mutex::new(Session::default()),
+			user_info_fetches: Coalescer::new(),
+			elo_fetches: Coalescer::new(),
+			match_fetches: Coalescer::new()
 		}
 	}
 	
 	/*
 	 * Fetches user display name and id by the given name.
 	 */
-	pub fn user_info<S>(&mut self, name: S) -> Option<(String, String)> where S: AsRef<str> {
+	pub fn user_info<S>(&self, name: S) -> Result<(String, String), VooblyError> where S: AsRef<str> {
 		let name = name.as_ref();
 		
-		if let Some(id_name) = self.id_cache.get(&name.to_uppercase()) {
-			return Some(id_name.clone());
+		if let Some(id_name) = self.id_cache.lock().unwrap().get(&name.to_uppercase()) {
+			return Ok(id_name);
 		}
 		
-		let url = format!("http://www.voobly.com/api/finduser/{}?key={}", name, self.key);
-		let response = request::get(&url)?;
-		let response = parse_response(&response);
-		let id = response.get("uid").map(ToString::to_string)?;
-		let actual_name = response.get("name").map(ToString::to_string)?;
+		if self.unknown_user_cache.lock().unwrap().get(&name.to_uppercase()).is_some() {
+			return Err(VooblyError::UnknownUser);
+		}
 		
-		if !id.is_empty() && !actual_name.is_empty() {
-			self.id_cache.insert(name.to_uppercase(), (id.clone(), actual_name.clone()));
-		} else {
-			self.id_cache.remove(&name.to_uppercase());
+		if self.key.is_empty() {
+			return Err(VooblyError::NotLoggedIn(String::from("VOOBLY_API_KEY is not set")));
 		}
 		
-		Some((id, actual_name))
+		self.user_info_fetches.run(name.to_uppercase(), || {
+			let url = format!("http://www.voobly.com/api/finduser/{}?key={}", name, self.key);
+			let response = request::get(&url).map_err(VooblyError::Unavailable)?;
+			let response = parse_response(&response);
+			
+			if let (Some(id), Some(actual_name)) = (response.get("uid"), response.get("name")) {
+				self.id_cache.lock().unwrap().insert(name.to_uppercase(), (id.to_string(), actual_name.to_string()));
+				
+				Ok((id.to_string(), actual_name.to_string()))
+			} else {
+				self.unknown_user_cache.lock().unwrap().insert(name.to_uppercase(), ());
+				
+				Err(VooblyError::UnknownUser)
+			}
+		})
 	}
 	
 	/*
 	 * Fetches user elo by the given user id.
+	 * If the user is not rated in the ladder, None is returned.
 	 * Caches the elo for a certain amount of time.
 	 */
-	pub fn elo<S, T>(&mut self, id: S, ladder: T) -> Option<String> where S: AsRef<str>, T: AsRef<str> {
+	pub fn elo<S, T>(&self, id: S, ladder: T) -> Result<Option<String>, VooblyError> where S: AsRef<str>, T: AsRef<str> {
 		let id = id.as_ref();
 		let ladder = ladder.as_ref();
 		let id_ladder_tuple = (id.to_uppercase(), ladder.to_uppercase());
 		
-		if let Some((id_ladder_tuple, (elo, timestamp))) = self.elo_cache.remove_entry(&id_ladder_tuple) {
-			if timestamp.elapsed() < Self::ELO_CACHE_DURATION {
-				self.elo_cache.insert(id_ladder_tuple, (elo.clone(), timestamp));
-				
-				return Some(elo);
-			}
+		if let Some(elo) = self.elo_cache.lock().unwrap().get(&id_ladder_tuple) {
+			return Ok(Some(elo));
 		}
 		
-		let url = format!("http://www.voobly.com/api/ladder/{}?key={}&uid={}", ladder, self.key, id);
-		let response = request::get(&url)?;
-		let response = parse_response(&response);
-		let elo = response.get("rating").map(ToString::to_string);
+		self.elo_fetches.run(id_ladder_tuple.clone(), || {
+			let url = format!("http://www.voobly.com/api/ladder/{}?key={}&uid={}", ladder, self.key, id);
+			let response = request::get(&url).map_err(VooblyError::Unavailable)?;
+			let response = parse_response(&response);
+			let elo = response.get("rating").map(ToString::to_string);
+			
+			if let Some(elo) = elo.clone() {
+				self.elo_cache.lock().unwrap().insert(id_ladder_tuple, elo);
+			}
+			
+			Ok(elo)
+		})
+	}
+	
+	/*
+	 * Fetches the match table of a page of the match history of the given user id.
+	 * Caches the table for a certain amount of time.
+	 */
+	pub fn matches<S>(&self, id: S, page: u16) -> Result<Table, VooblyError> where S: AsRef<str> {
+		let id = id.as_ref();
+		
+		let id_page_tuple = (id.to_uppercase(), page);
 		
-		if let Some(elo) = elo.clone() {
-			self.elo_cache.insert(id_ladder_tuple, (elo, Instant::now()));
+		if let Some(match_data) = self.match_cache.lock().unwrap().get(&id_page_tuple) {
+			return Ok(match_data);
 		}
 		
-		elo
+		self.match_fetches.run(id_page_tuple.clone(), || {
+			let url = format!("https://www.voobly.com/profile/view/{}/Matches/games/matches/user/{}/0/{}", id, id, page);
+			let matches = self.get_logged_in(&url)?;
+			let match_data = Table::find_first(&matches).ok_or(VooblyError::NoMatches)?;
+			
+			self.match_cache.lock().unwrap().insert(id_page_tuple, match_data.clone());
+			
+			Ok(match_data)
+		})
 	}
 	
-	pub fn matches<S>(&mut self, id: S, page: u16) -> Option<Table> where S: AsRef<str> {
-		let id = id.as_ref();
+	/*
+	 * Fetches the match tables of the first pages of the match history of the given user id.
+	 * Stops early at the end of the history.
+	 */
+	pub fn match_history<S>(&self, id: S, pages: u16) -> Result<Vec<Table>, VooblyError> where S: AsRef<str> {
+		let mut tables = Vec::new();
 		
-		if let Some((id, (match_data, timestamp))) = self.match_cache.remove_entry(id) {
-			if timestamp.elapsed() < Self::MATCH_CACHE_DURATION {
-				self.match_cache.insert(id, (match_data.clone(), timestamp));
+		for page in 0 .. pages {
+			match self.matches(id.as_ref(), page) {
+				/* The first row holds the column titles */
+				Ok(ref match_data) if match_data.iter().count() <= 1 => break,
+				Ok(match_data) => tables.push(match_data),
+				Err(VooblyError::NoMatches) if page > 0 => break,
+				Err(error) => return Err(error)
+			}
+		}
+		
+		Ok(tables)
+	}
+	
+	/*
+	 * Fetches a page which is only visible when logged in.
+	 * The session is kept between requests. If Voobly answers with the login page, the session expired and is renewed once.
+	 */
+	fn get_logged_in(&self, url: &str) -> Result<String, VooblyError> {
+		/* At most one retry with a fresh session */
+		for _ in 0 .. 2 {
+			let (mut cookies, generation, fresh_session) = self.session_cookies()?;
+			let page = request::get_with_cookies(url, &mut cookies).map_err(VooblyError::Unavailable)?;
+			let mut session = self.session.lock().unwrap();
+			/* If another request logged in again meanwhile, its session must be neither overwritten nor dropped */
+			let same_session = session.generation == generation;
+			
+			if !is_login_page(&page) {
+				if same_session {
+					/* Keep cookies Voobly updated with this response */
+					session.cookies = Some(cookies);
+				}
+				
+				session.login_succeeded();
+				
+				return Ok(page);
+			}
+			
+			if !same_session {
+				continue;
+			}
+			
+			if fresh_session {
+				/* Still not logged in right after logging in, so the login itself failed */
+				session.login_failed();
 				
-				return Some(match_data);
+				return Err(VooblyError::NotLoggedIn(String::from("Voobly rejected the login")));
 			}
+			
+			session.cookies = None;
 		}
 		
-		let mut cookie_jar = CookieJar::new();
-		let url = format!("https://www.voobly.com/profile/view/{}/Matches/games/matches/user/{}/0/{}", id, id, page);
-		let form_data = vec![("username", self.username.as_str()), ("password", self.password.as_str())];
+		Err(VooblyError::NotLoggedIn(String::from("The session expired")))
+	}
+	
+	/*
+	 * Gets the cookies of the current session, logging in if there is none.
+	 * Also returns the generation of the session and whether it was just created.
+	 */
+	fn session_cookies(&self) -> Result<(CookieJar, u64, bool), VooblyError> {
+		let mut session = self.session.lock().unwrap();
+		
+		if let Some(ref cookies) = session.cookies {
+			return Ok((cookies.clone(), session.generation, false));
+		}
+		
+		let cookies = self.login(&mut session)?;
 		
-		request::get_with_cookies("https://www.voobly.com", &mut cookie_jar)?;
-		request::post_with_cookies("https://www.voobly.com/login/auth", &mut cookie_jar, form_data)?;
+		session.cookies = Some(cookies.clone());
+		session.generation += 1;
 		
-		let matches = request::get_with_cookies(&url, &mut cookie_jar)?;
-		let match_data = Table::find_first(&matches);
+		Ok((cookies, session.generation, true))
+	}
+	
+	/*
+	 * Logs into Voobly and returns the cookies of the new session.
+	 * After failed logins, no login is attempted until the backoff has passed.
+	 */
+	fn login(&self, session: &mut Session) -> Result<CookieJar, VooblyError> {
+		if self.username.is_empty() || self.password.is_empty() {
+			return Err(VooblyError::NotLoggedIn(String::from("VOOBLY_USER or VOOBLY_PASS is not set")));
+		}
 		
-		if let Some(match_data) = match_data.clone() {
-			self.match_cache.insert(id.to_uppercase(), (match_data, Instant::now()));
+		if let Some(retry) = session.login_retry {
+			let now = Instant::now();
+			
+			if now < retry {
+				return Err(VooblyError::NotLoggedIn(format!("Waiting {}s before the next login attempt", (retry - now).as_secs())));
+			}
 		}
 		
-		match_data
+		let mut cookies = CookieJar::new();
+		let form_data = vec![("username", self.username.as_str()), ("password", self.password.as_str())];
+		let response = request::get_with_cookies("https://www.voobly.com", &mut cookies)
+			.and_then(|_| request::post_with_cookies("https://www.voobly.com/login/auth", &mut cookies, form_data));
+		
+		match response {
+			Ok(_) => Ok(cookies),
+			Err(e) => {
+				session.login_failed();
+				
+				Err(VooblyError::Unavailable(e))
+			}
+		}
 	}
 }
 
+/*
+ * Checks if Voobly answered with its login form instead of the requested page.
+ */
+fn is_login_page(page: &str) -> bool {
+	page.contains("/login/auth")
+}
+
 /*
  * Parses these weird Voobly responses into a map.
  * Responses look like this:
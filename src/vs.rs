@@ -0,0 +1,105 @@
+use std::sync::RwLock;
+
+use rocket::State;
+
+use util::{
+	self,
+	NightbotHeaderFields
+};
+use data::{
+	GameData,
+	Unit
+};
+
+/*
+ * Outcome of one unit attacking another one.
+ */
+enum Engagement {
+	Kills(u32, f32),
+	CannotAttack,
+	MissingStats
+}
+
+/*
+ * Calculates the damage per hit of the attacker against the defender.
+ * Units with more than 1 range deal pierce damage, all others melee damage.
+ */
+fn damage_per_hit(attacker: &Unit, defender: &Unit) -> Option<u32> {
+	let attack = attacker.attack()? as i32;
+	let (melee_armor, pierce_armor) = defender.armor()?;
+	let armor = if attacker.range().map(|range| range > 1.0).unwrap_or(false) {
+		pierce_armor
+	} else {
+		melee_armor
+	};
+	let bonus: i32 = attacker.attack_bonuses().iter()
+		.filter(|bonus| bonus.applies_to(defender))
+		.map(|bonus| bonus.amount)
+		.sum();
+	
+	Some(((attack - armor).max(0) + bonus.max(0)).max(1) as u32)
+}
+
+/*
+ * Calculates the hits and the time (in seconds) the attacker needs to kill the defender.
+ * The first hit lands immediately, every further hit after the attack speed of the attacker.
+ */
+fn engage(attacker: &Unit, defender: &Unit) -> Engagement {
+	if attacker.attack().unwrap_or(0) == 0 {
+		return Engagement::CannotAttack;
+	}
+	
+	if let (Some(damage), Some(hp), Some(attack_speed)) = (damage_per_hit(attacker, defender), defender.hp(), attacker.attack_speed()) {
+		let hits = ((hp + damage - 1) / damage).max(1);
+		
+		Engagement::Kills(hits, (hits - 1) as f32 * attack_speed)
+	} else {
+		Engagement::MissingStats
+	}
+}
+
+fn describe(attacker: &Unit, engagement: &Engagement) -> String {
+	match engagement {
+		Engagement::Kills(1, _) => format!("{} needs 1 hit", attacker.name),
+		Engagement::Kills(hits, time) => format!("{} needs {} hits ({:.1}s)", attacker.name, hits, time),
+		Engagement::CannotAttack => format!("{} cannot attack", attacker.name),
+		Engagement::MissingStats => format!("{} lacks stats", attacker.name)
+	}
+}
+
+/*
+ * Estimates the outcome of a 1v1 between two units without upgrades.
+ */
+fn fetch_matchup(data: &GameData, name_a: &str, name_b: &str) -> String {
+	let (unit_a, unit_b) = match (data.unit_by_name(name_a), data.unit_by_name(name_b)) {
+		(Some(unit_a), Some(unit_b)) => (unit_a, unit_b),
+		(None, _) => return util::with_suggestions(format!("{} does not exist.", name_a), &data.unit_suggestions(name_a)),
+		(_, None) => return util::with_suggestions(format!("{} does not exist.", name_b), &data.unit_suggestions(name_b))
+	};
+	let a_vs_b = engage(unit_a, unit_b);
+	let b_vs_a = engage(unit_b, unit_a);
+	let winner = match (&a_vs_b, &b_vs_a) {
+		(Engagement::Kills(_, time_a), Engagement::Kills(_, time_b)) if time_a < time_b => format!("{} wins.", unit_a.name),
+		(Engagement::Kills(_, time_a), Engagement::Kills(_, time_b)) if time_a > time_b => format!("{} wins.", unit_b.name),
+		(Engagement::Kills(..), Engagement::Kills(..)) => String::from("It's a draw."),
+		(Engagement::Kills(..), Engagement::CannotAttack) => format!("{} wins.", unit_a.name),
+		(Engagement::CannotAttack, Engagement::Kills(..)) => format!("{} wins.", unit_b.name),
+		_ => String::from("No winner.")
+	};
+	
+	format!("{} vs {} (no upgrades): {}, {}. {}", unit_a.name, unit_b.name, describe(unit_a, &a_vs_b), describe(unit_b, &b_vs_a), winner)
+}
+
+/*
+ * Request handler for the vs resource.
+ * Calculates hits and time to kill for two units fighting each other.
+ * "data_lock" is the GameData struct kept persistent between requests by Rocket.
+ * Only accepts the request if the Nightbot headers are present.
+ */
+#[get("/vs/<unit_a>/<unit_b>")]
+pub fn vs(data_lock: State<RwLock<GameData>>, unit_a: String, unit_b: String, nightbot_headers: NightbotHeaderFields) -> String {
+	let data = data_lock.read().unwrap();
+	let matchup = fetch_matchup(&data, &unit_a, &unit_b);
+	
+	util::create_response(matchup, &nightbot_headers)
+}